@@ -1,13 +1,19 @@
 fn main() {
+    let mut config = prost_build::Config::new();
+    // The upstream docs of the error reasons contain indented examples that rustdoc would run as
+    // doctests
+    config.disable_comments([".authzed.api.v1.ErrorReason"]);
     tonic_build::configure()
         .include_file("_includes.rs")
-        .build_server(false)
-        .compile(
+        .build_server(std::env::var_os("CARGO_FEATURE_TEST_SERVER").is_some())
+        .compile_with_config(
+            config,
             &[
                 "proto/authzed/api/v1/permission_service.proto",
                 "proto/authzed/api/v1/watch_service.proto",
                 "proto/authzed/api/v1/experimental_service.proto",
                 "proto/authzed/api/v1/schema_service.proto",
+                "proto/authzed/api/v1/error_reason.proto",
             ],
             &["proto"],
         )
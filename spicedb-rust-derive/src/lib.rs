@@ -0,0 +1,231 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields, LitStr, Type};
+
+/// Implements `Entity`, and `Resource` if `permissions` is given.
+/// ```rust,ignore
+/// #[derive(Entity)]
+/// #[spicedb(
+///     object_type = "document",
+///     id = String,
+///     relations = DocumentRelation,
+///     permissions = DocumentPermission
+/// )]
+/// struct Document;
+/// ```
+/// `relations` defaults to `NoRelations`.
+#[proc_macro_derive(Entity, attributes(spicedb))]
+pub fn derive_entity(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_entity(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `Actor` for a struct wrapping an id, or an enum whose variants each wrap an id.
+/// The entity type of the subject is set with `entity = ...` and defaults to `Self`, a subject
+/// relation can be set with `relation = "..."`.
+/// ```rust,ignore
+/// #[derive(Actor)]
+/// enum SystemActor {
+///     #[spicedb(entity = User)]
+///     User(Uuid),
+///     #[spicedb(entity = Organization, relation = "member")]
+///     Organization(String),
+/// }
+/// ```
+#[proc_macro_derive(Actor, attributes(spicedb))]
+pub fn derive_actor(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_actor(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct EntityArgs {
+    object_type: Option<LitStr>,
+    id: Option<Type>,
+    relations: Option<Type>,
+    permissions: Option<Type>,
+}
+
+#[derive(Default)]
+struct ActorArgs {
+    entity: Option<Type>,
+    relation: Option<LitStr>,
+}
+
+fn parse_entity_args(attrs: &[Attribute]) -> syn::Result<EntityArgs> {
+    let mut args = EntityArgs::default();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("spicedb")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("object_type") {
+                args.object_type = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("id") {
+                args.id = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("relations") {
+                args.relations = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("permissions") {
+                args.permissions = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("entity") || meta.path.is_ident("relation") {
+                // Belongs to `#[derive(Actor)]`
+                meta.value()?.parse::<TokenStream2>()?;
+            } else {
+                return Err(meta.error("unknown spicedb attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(args)
+}
+
+fn parse_actor_args(attrs: &[Attribute]) -> syn::Result<ActorArgs> {
+    let mut args = ActorArgs::default();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("spicedb")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("entity") {
+                args.entity = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("relation") {
+                args.relation = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("object_type")
+                || meta.path.is_ident("id")
+                || meta.path.is_ident("relations")
+                || meta.path.is_ident("permissions")
+            {
+                // Belongs to `#[derive(Entity)]`
+                meta.value()?.parse::<TokenStream2>()?;
+            } else {
+                return Err(meta.error("unknown spicedb attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(args)
+}
+
+fn expand_entity(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let args = parse_entity_args(&input.attrs)?;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let object_type = args.object_type.ok_or_else(|| {
+        syn::Error::new_spanned(name, "missing `#[spicedb(object_type = \"...\")]`")
+    })?;
+    let id = args
+        .id
+        .ok_or_else(|| syn::Error::new_spanned(name, "missing `#[spicedb(id = ...)]`"))?;
+    let relations = args
+        .relations
+        .map(|r| quote!(#r))
+        .unwrap_or_else(|| quote!(::spicedb_rust::NoRelations));
+
+    let resource = args.permissions.map(|permissions| {
+        quote! {
+            impl #impl_generics ::spicedb_rust::Resource for #name #ty_generics #where_clause {
+                type Permissions = #permissions;
+            }
+        }
+    });
+    Ok(quote! {
+        impl #impl_generics ::spicedb_rust::Entity for #name #ty_generics #where_clause {
+            type Relations = #relations;
+            type Id = #id;
+
+            fn object_type() -> &'static str {
+                #object_type
+            }
+        }
+
+        #resource
+    })
+}
+
+/// Builds the pattern binding the single id field of a struct or variant to `id`.
+fn id_pattern(fields: &Fields, span: &impl quote::ToTokens) -> syn::Result<TokenStream2> {
+    if fields.len() != 1 {
+        return Err(syn::Error::new_spanned(
+            span,
+            "Actor can only be derived for a single field wrapping the id",
+        ));
+    }
+    Ok(match fields {
+        Fields::Named(named) => {
+            let field = named.named.first().unwrap().ident.as_ref().unwrap();
+            quote!({ #field: id })
+        }
+        _ => quote!((id)),
+    })
+}
+
+fn subject_reference(args: &ActorArgs) -> TokenStream2 {
+    let entity = args
+        .entity
+        .as_ref()
+        .map(|e| quote!(#e))
+        .unwrap_or_else(|| quote!(Self));
+    let relation = match &args.relation {
+        Some(relation) => quote!(::std::option::Option::Some(#relation)),
+        None => quote!(::std::option::Option::None::<&'static str>),
+    };
+    quote! {
+        ::spicedb_rust::spicedb::subject_reference_raw(
+            ::std::clone::Clone::clone(id),
+            <#entity as ::spicedb_rust::Entity>::object_type(),
+            #relation,
+        )
+    }
+}
+
+fn expand_actor(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let args = parse_actor_args(&input.attrs)?;
+            let pattern = id_pattern(&data.fields, name)?;
+            let subject = subject_reference(&args);
+            quote! {
+                let Self #pattern = self;
+                #subject
+            }
+        }
+        Data::Enum(data) => {
+            let arms = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let args = parse_actor_args(&variant.attrs)?;
+                    if args.entity.is_none() {
+                        return Err(syn::Error::new_spanned(
+                            variant,
+                            "missing `#[spicedb(entity = ...)]` on variant",
+                        ));
+                    }
+                    let ident = &variant.ident;
+                    let pattern = id_pattern(&variant.fields, variant)?;
+                    let subject = subject_reference(&args);
+                    Ok(quote!(Self::#ident #pattern => #subject,))
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "Actor can't be derived for unions",
+            ))
+        }
+    };
+    Ok(quote! {
+        impl #impl_generics ::spicedb_rust::Actor for #name #ty_generics #where_clause {
+            fn to_subject(&self) -> ::spicedb_rust::spicedb::SubjectReference {
+                #body
+            }
+        }
+    })
+}
@@ -0,0 +1,196 @@
+//! Self-contained backup file holding the schema text, the `ZedToken` of the snapshot and every
+//! relationship of a SpiceDB instance, including caveat names and contexts.
+//!
+//! The file starts with `MAGIC`, followed by frames of a big-endian `u32` length and a protobuf
+//! message. The first frame is a `BackupHeader`, followed by a frame per `spicedb::Relationship`,
+//! an empty frame marking their end and a `BackupTrailer` with their count, so a file cut off
+//! anywhere is rejected instead of restoring part of the relationships. `restore` reads the file
+//! twice, checking all of it before it touches the schema or imports anything.
+//! This format is specific to this crate and can't be read by `zed backup restore`.
+
+use std::io::SeekFrom;
+
+use futures::{SinkExt, TryStreamExt};
+use prost::Message;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+
+use crate::spicedb::wrappers::Consistency;
+use crate::{spicedb, SpiceDBClient};
+
+const MAGIC: &[u8; 20] = b"SPICEDB-RUST-BACKUP\n";
+const VERSION: u32 = 1;
+
+#[derive(Clone, PartialEq, Message)]
+struct BackupHeader {
+    #[prost(uint32, tag = "1")]
+    version: u32,
+    #[prost(string, tag = "2")]
+    schema_text: String,
+    #[prost(message, optional, tag = "3")]
+    zed_token: Option<spicedb::ZedToken>,
+}
+
+/// Length of the frame ending the relationships, an exported relationship is never empty.
+const END_OF_RELATIONSHIPS: u32 = 0;
+
+#[derive(Clone, PartialEq, Message)]
+struct BackupTrailer {
+    #[prost(uint64, tag = "1")]
+    relationships: u64,
+}
+
+async fn write_frame(
+    writer: &mut (impl AsyncWrite + Unpin),
+    message: &impl Message,
+) -> anyhow::Result<()> {
+    let bytes = message.encode_to_vec();
+    writer.write_u32(u32::try_from(bytes.len())?).await?;
+    writer.write_all(&bytes).await?;
+    Ok(())
+}
+
+fn truncated() -> anyhow::Error {
+    anyhow::anyhow!("Backup file is truncated")
+}
+
+/// Reads the length of the next frame, `None` if the reader ended before any of its bytes.
+async fn read_len(reader: &mut (impl AsyncRead + Unpin)) -> anyhow::Result<Option<u32>> {
+    let mut prefix = [0; 4];
+    let mut read = 0;
+    while read < prefix.len() {
+        match reader.read(&mut prefix[read..]).await? {
+            0 if read == 0 => return Ok(None),
+            0 => return Err(truncated()),
+            n => read += n,
+        }
+    }
+    Ok(Some(u32::from_be_bytes(prefix)))
+}
+
+async fn read_message<M>(reader: &mut (impl AsyncRead + Unpin), len: u32) -> anyhow::Result<M>
+where
+    M: Message + Default,
+{
+    let mut bytes = vec![0; len as usize];
+    match reader.read_exact(&mut bytes).await {
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Err(truncated()),
+        result => result?,
+    };
+    Ok(M::decode(bytes.as_slice())?)
+}
+
+async fn read_frame<M>(reader: &mut (impl AsyncRead + Unpin)) -> anyhow::Result<M>
+where
+    M: Message + Default,
+{
+    let len = read_len(reader).await?.ok_or_else(truncated)?;
+    read_message(reader, len).await
+}
+
+/// Writes the schema and all relationships as of a single snapshot, returns the `ZedToken` of
+/// that snapshot.
+pub(crate) async fn backup(
+    client: &SpiceDBClient,
+    writer: &mut (impl AsyncWrite + Unpin),
+) -> anyhow::Result<spicedb::ZedToken> {
+    let schema = client.read_schema().await?;
+    writer.write_all(MAGIC).await?;
+    let header = BackupHeader {
+        version: VERSION,
+        schema_text: schema.schema_text,
+        zed_token: Some(schema.read_at.clone()),
+    };
+    write_frame(writer, &header).await?;
+
+    let mut relationships = Box::pin(
+        client
+            .bulk_export_relationships(Consistency::AtExactSnapshot(schema.read_at.clone()))
+            .await?,
+    );
+    let mut count = 0;
+    while let Some(relationship) = relationships.try_next().await? {
+        write_frame(writer, &spicedb::Relationship::from(relationship)).await?;
+        count += 1;
+    }
+    writer.write_u32(END_OF_RELATIONSHIPS).await?;
+    write_frame(
+        writer,
+        &BackupTrailer {
+            relationships: count,
+        },
+    )
+    .await?;
+    writer.flush().await?;
+    Ok(schema.read_at)
+}
+
+/// Reads the header and checks that every relationship frame decodes and that the trailer
+/// matches, then seeks back to the first relationship. Returns the header and the number of
+/// relationships.
+async fn verify(
+    reader: &mut (impl AsyncRead + AsyncSeek + Unpin),
+) -> anyhow::Result<(BackupHeader, u64)> {
+    let mut magic = [0; MAGIC.len()];
+    reader.read_exact(&mut magic).await?;
+    if &magic != MAGIC {
+        anyhow::bail!("Not a spicedb-rust backup file");
+    }
+    let header = read_frame::<BackupHeader>(reader).await?;
+    if header.version != VERSION {
+        anyhow::bail!("Unsupported backup version: {}", header.version);
+    }
+    let relationships = reader.stream_position().await?;
+
+    let mut count = 0;
+    loop {
+        match read_len(reader).await?.ok_or_else(truncated)? {
+            END_OF_RELATIONSHIPS => break,
+            len => {
+                read_message::<spicedb::Relationship>(reader, len).await?;
+                count += 1;
+            }
+        }
+    }
+    let trailer = read_frame::<BackupTrailer>(reader).await?;
+    if trailer.relationships != count {
+        anyhow::bail!(
+            "Backup file holds {} relationships, its trailer expects {}",
+            count,
+            trailer.relationships
+        );
+    }
+    if reader.read(&mut [0]).await? != 0 {
+        anyhow::bail!("Backup file has data after its trailer");
+    }
+    reader.seek(SeekFrom::Start(relationships)).await?;
+    Ok((header, count))
+}
+
+/// Checks the whole backup, then writes its schema and imports all of its relationships in a
+/// single bulk import, returns the number of relationships loaded.
+pub(crate) async fn restore(
+    client: &SpiceDBClient,
+    reader: &mut (impl AsyncRead + AsyncSeek + Unpin),
+    chunk_size: usize,
+) -> anyhow::Result<u64> {
+    let (header, count) = verify(reader).await?;
+    if !header.schema_text.is_empty() {
+        client.write_schema(header.schema_text).await?;
+    }
+
+    // Feeding the import through a bounded channel keeps memory flat, the file was checked above
+    // so reading it again only fails on an I/O error
+    let (mut tx, rx) = futures::channel::mpsc::channel::<spicedb::Relationship>(chunk_size);
+    let mut request = client.bulk_import_relationships_request();
+    request.chunk_size(chunk_size);
+    let import = async { Ok::<_, anyhow::Error>(request.send_stream(rx).await?) };
+    let feed = async move {
+        for _ in 0..count {
+            let len = read_len(reader).await?.ok_or_else(truncated)?;
+            tx.send(read_message(reader, len).await?).await?;
+        }
+        Ok::<_, anyhow::Error>(())
+    };
+    let (num_loaded, ()) = futures::try_join!(import, feed)?;
+    Ok(num_loaded)
+}
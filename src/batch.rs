@@ -0,0 +1,146 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+
+use crate::grpc::GrpcResult;
+use crate::permission::{CheckBulkPermissionsRequest, SpiceDBPermissionClient};
+use crate::spicedb::wrappers::Permissionship;
+use crate::{spicedb, Error, RetryPolicy};
+
+type Waiter = oneshot::Sender<GrpcResult<Permissionship>>;
+
+/// Checks waiting to be sent together, only checks with the same consistency and retry policy
+/// share a batch.
+#[derive(Debug)]
+struct Batch {
+    id: u64,
+    consistency: Option<spicedb::Consistency>,
+    retry: RetryPolicy,
+    items: Vec<spicedb::CheckBulkPermissionsRequestItem>,
+    waiters: Vec<Waiter>,
+}
+
+#[derive(Debug, Default)]
+struct Pending {
+    batches: Vec<Batch>,
+    next_id: u64,
+}
+
+/// Collects the checks of concurrent `check_permission` calls and sends them as one
+/// `CheckBulkPermissions`, once `window` passed since the first check of a batch or it reached
+/// `max_batch_size` checks.
+#[derive(Debug)]
+pub(crate) struct CheckBatcher {
+    client: SpiceDBPermissionClient,
+    window: Duration,
+    max_batch_size: usize,
+    pending: Mutex<Pending>,
+}
+
+impl CheckBatcher {
+    pub(crate) fn new(
+        client: SpiceDBPermissionClient,
+        window: Duration,
+        max_batch_size: usize,
+    ) -> Self {
+        CheckBatcher {
+            client,
+            window,
+            max_batch_size: max_batch_size.max(1),
+            pending: Mutex::default(),
+        }
+    }
+
+    /// Adds the check to a batch and waits for the batch's response, `consistency` has to be
+    /// resolved already. The batch is sent with `retry`, the policy of the calling client.
+    pub(crate) async fn check(
+        self: &Arc<Self>,
+        item: spicedb::CheckBulkPermissionsRequestItem,
+        consistency: Option<spicedb::Consistency>,
+        retry: RetryPolicy,
+    ) -> GrpcResult<Permissionship> {
+        let (waiter, result) = oneshot::channel();
+        let (full, opened) = {
+            let mut pending = self.pending.lock().unwrap();
+            let index = pending
+                .batches
+                .iter()
+                .position(|batch| batch.consistency == consistency && batch.retry.same_as(&retry));
+            let index = match index {
+                Some(index) => index,
+                None => {
+                    let id = pending.next_id;
+                    pending.next_id += 1;
+                    pending.batches.push(Batch {
+                        id,
+                        consistency,
+                        retry,
+                        items: vec![],
+                        waiters: vec![],
+                    });
+                    pending.batches.len() - 1
+                }
+            };
+            let batch = &mut pending.batches[index];
+            batch.items.push(item);
+            batch.waiters.push(waiter);
+            let opened = (batch.items.len() == 1).then_some(batch.id);
+            let full = (batch.items.len() >= self.max_batch_size)
+                .then(|| pending.batches.swap_remove(index));
+            (full, opened)
+        };
+
+        // Sent from a task of its own, so a caller giving up doesn't cancel the others' checks
+        if let Some(batch) = full {
+            let batcher = self.clone();
+            tokio::spawn(async move { batcher.send(batch).await });
+        } else if let Some(id) = opened {
+            let batcher = self.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(batcher.window).await;
+                if let Some(batch) = batcher.take(id) {
+                    batcher.send(batch).await;
+                }
+            });
+        }
+        result
+            .await
+            .map_err(|_| Error::invalid_response("batched check was dropped"))?
+    }
+
+    /// Removes the batch `id` unless it was already sent because it was full.
+    fn take(&self, id: u64) -> Option<Batch> {
+        let mut pending = self.pending.lock().unwrap();
+        let index = pending.batches.iter().position(|batch| batch.id == id)?;
+        Some(pending.batches.swap_remove(index))
+    }
+
+    async fn send(&self, batch: Batch) {
+        let mut request = CheckBulkPermissionsRequest::new(self.client.clone());
+        request
+            .retry_policy(batch.retry)
+            .consistency_raw(batch.consistency);
+        for item in batch.items {
+            request.add_item_raw(item);
+        }
+        match request.send().await {
+            Ok(resp) => {
+                let mut results = resp.results.into_iter();
+                for waiter in batch.waiters {
+                    let result = results.next().unwrap_or_else(|| {
+                        Err(Error::invalid_response(
+                            "CheckBulkPermissions returned fewer results than items",
+                        ))
+                    });
+                    let _ = waiter.send(result);
+                }
+            }
+            Err(err) => {
+                for waiter in batch.waiters {
+                    let _ = waiter.send(Err(err.duplicate()));
+                }
+            }
+        }
+    }
+}
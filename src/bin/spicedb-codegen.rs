@@ -0,0 +1,53 @@
+//! Generates the rust types for the schema deployed in SpiceDB, connecting with the
+//! `SPICEDB_ENDPOINT` and `SPICEDB_TOKEN` env variables.
+
+use anyhow::{bail, Context};
+use spicedb_rust::codegen::Codegen;
+use spicedb_rust::SpiceDBClient;
+
+const USAGE: &str = "\
+Usage: spicedb-codegen [OPTIONS] [OUTPUT]
+
+Writes the generated module to OUTPUT, or stdout if omitted.
+
+Options:
+    --default-id-type <TYPE>       Id type of all definitions without an override [default: String]
+    --id-type <DEFINITION>=<TYPE>  Id type of a single definition, can be repeated
+    -h, --help                     Print this message";
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let mut codegen = Codegen::new();
+    let mut output = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            "--default-id-type" => {
+                let id_type = args.next().context("--default-id-type requires a value")?;
+                codegen.default_id_type(id_type);
+            }
+            "--id-type" => {
+                let value = args.next().context("--id-type requires a value")?;
+                let (definition, id_type) = value
+                    .split_once('=')
+                    .context("--id-type expects <DEFINITION>=<TYPE>")?;
+                codegen.id_type(definition, id_type);
+            }
+            _ if arg.starts_with('-') => bail!("Unknown option `{}`\n\n{}", arg, USAGE),
+            _ if output.is_none() => output = Some(arg),
+            _ => bail!("Unexpected argument `{}`\n\n{}", arg, USAGE),
+        }
+    }
+
+    let client = SpiceDBClient::from_env().await?;
+    let code = codegen.render_from_client(&client).await?;
+    match output {
+        Some(path) => std::fs::write(path, code)?,
+        None => print!("{}", code),
+    }
+    Ok(())
+}
@@ -0,0 +1,398 @@
+//! Local cache of `CheckPermission` results for hot paths that check the same permissions over
+//! and over.
+//!
+//! Results are cached for a TTL and evicted as soon as the `WatchService` reports a change to a
+//! relationship on the same resource type. Nothing is cached before the watch started or after
+//! it failed for good. A permission that is granted through relationships
+//! of other types, e.g. `document#read` through `group#member`, is only refreshed by the TTL, so
+//! pick a TTL your application can tolerate as staleness.
+//!
+//! Misses are checked with the consistency of the wrapped client, i.e. its default or the
+//! `ZedToken` of its session, but hits are returned whatever that consistency asks for until the
+//! watch or the TTL evicts them. Use the underlying `SpiceDBClient` for checks that have to see a
+//! specific write.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use prost::Message;
+use tokio::task::JoinHandle;
+use tokio_stream::StreamExt;
+
+use crate::grpc::GrpcResult;
+use crate::spicedb::check_permission_response::Permissionship;
+use crate::spicedb::{self, object_reference};
+use crate::watch::InMemoryCheckpointStore;
+use crate::{Actor, Permission, Resource, SpiceDBClient};
+
+/// Configures a `CachedSpiceDBClient`, created by `CachedSpiceDBClient::builder`.
+#[derive(Clone, Debug)]
+pub struct CachedSpiceDBClientBuilder {
+    client: SpiceDBClient,
+    ttl: Duration,
+    max_entries: usize,
+}
+
+impl CachedSpiceDBClientBuilder {
+    /// How long a result is served from the cache, 10s by default.
+    pub fn ttl(&mut self, ttl: Duration) -> &mut Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Number of results kept, the oldest ones are evicted first. 10,000 by default.
+    pub fn max_entries(&mut self, max_entries: usize) -> &mut Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Starts watching for relationship changes in the background, the watch is stopped when
+    /// the last clone of the client is dropped. Needs a tokio runtime.
+    pub fn build(&self) -> CachedSpiceDBClient {
+        let cache = Arc::new(Cache {
+            ttl: self.ttl,
+            max_entries: self.max_entries,
+            state: Mutex::default(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        });
+        let watch = tokio::spawn(watch(self.client.clone(), cache.clone()));
+        CachedSpiceDBClient {
+            client: self.client.clone(),
+            cache,
+            _watch: Arc::new(WatchTask(watch)),
+        }
+    }
+}
+
+/// Counters of a `CachedSpiceDBClient`, see `CachedSpiceDBClient::stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Results currently cached, including expired ones that weren't evicted yet
+    pub entries: usize,
+    /// Results evicted because a relationship of their resource type changed
+    pub invalidations: u64,
+    /// `true` while changes are watched, results are only cached while they are
+    pub watching: bool,
+}
+
+/// `SpiceDBClient` wrapper caching the results of `check_permission`, see the `cache` module.
+///
+/// ```ignore
+/// let cached = CachedSpiceDBClient::builder(client)
+///     .ttl(Duration::from_secs(30))
+///     .max_entries(100_000)
+///     .build();
+/// cached.check_permission::<Document>(&actor, "readme", DocumentPermission::Read).await?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct CachedSpiceDBClient {
+    client: SpiceDBClient,
+    cache: Arc<Cache>,
+    _watch: Arc<WatchTask>,
+}
+
+impl CachedSpiceDBClient {
+    pub fn builder(client: SpiceDBClient) -> CachedSpiceDBClientBuilder {
+        CachedSpiceDBClientBuilder {
+            client,
+            ttl: Duration::from_secs(10),
+            max_entries: 10_000,
+        }
+    }
+
+    /// The wrapped client, for requests that shouldn't be cached.
+    pub fn client(&self) -> &SpiceDBClient {
+        &self.client
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let state = self.cache.state.lock().unwrap();
+        CacheStats {
+            hits: self.cache.hits.load(Ordering::Relaxed),
+            misses: self.cache.misses.load(Ordering::Relaxed),
+            entries: state.entries.len(),
+            invalidations: state.invalidations,
+            watching: state.watching,
+        }
+    }
+
+    /// Evicts every cached result.
+    pub fn clear(&self) {
+        self.cache.state.lock().unwrap().clear();
+    }
+
+    pub async fn check_permission<R>(
+        &self,
+        actor: &impl Actor,
+        resource_id: impl Into<R::Id>,
+        permission: R::Permissions,
+    ) -> GrpcResult<bool>
+    where
+        R: Resource,
+    {
+        self.check::<R>(actor, resource_id.into(), permission, None)
+            .await
+    }
+
+    /// Checks a caveated permission, results are cached per `context`. Results that are
+    /// conditional on context missing from `context` are never cached.
+    pub async fn check_permission_with_context<R>(
+        &self,
+        actor: &impl Actor,
+        resource_id: impl Into<R::Id>,
+        permission: R::Permissions,
+        context: impl Into<prost_types::Struct>,
+    ) -> GrpcResult<bool>
+    where
+        R: Resource,
+    {
+        self.check::<R>(actor, resource_id.into(), permission, Some(context.into()))
+            .await
+    }
+
+    async fn check<R>(
+        &self,
+        actor: &impl Actor,
+        resource_id: R::Id,
+        permission: R::Permissions,
+        context: Option<prost_types::Struct>,
+    ) -> GrpcResult<bool>
+    where
+        R: Resource,
+    {
+        let subject = actor.to_subject();
+        let resource = object_reference::<R>(resource_id);
+        let permission = permission.name();
+        let key = CacheKey::new(&subject, &resource, permission, context.as_ref());
+        let generation = match self.cache.get(&key) {
+            Lookup::Hit(allowed) => return Ok(allowed),
+            Lookup::Miss(generation) => generation,
+        };
+
+        let mut request = self.client.check_permission_request::<R>();
+        request.subject(subject);
+        request.resource(resource);
+        request.permission_raw(permission);
+        if let Some(context) = context {
+            request.context(context);
+        }
+        let resp = request.send().await?;
+        let permissionship = Permissionship::try_from(resp.permissionship).ok();
+        let allowed = permissionship == Some(Permissionship::HasPermission);
+        if matches!(
+            permissionship,
+            Some(Permissionship::HasPermission | Permissionship::NoPermission)
+        ) {
+            self.cache.insert(key, allowed, generation);
+        }
+        Ok(allowed)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct CacheKey {
+    subject_type: String,
+    subject_id: String,
+    subject_relation: String,
+    resource_type: String,
+    resource_id: String,
+    permission: String,
+    context_hash: u64,
+}
+
+impl CacheKey {
+    fn new(
+        subject: &spicedb::SubjectReference,
+        resource: &spicedb::ObjectReference,
+        permission: &str,
+        context: Option<&prost_types::Struct>,
+    ) -> Self {
+        let object = subject.object.clone().unwrap_or_default();
+        // The fields of a `Struct` are a `BTreeMap`, so equal contexts encode to the same bytes
+        let mut hasher = DefaultHasher::new();
+        context.map(Message::encode_to_vec).hash(&mut hasher);
+        CacheKey {
+            subject_type: object.object_type,
+            subject_id: object.object_id,
+            subject_relation: subject.optional_relation.clone(),
+            resource_type: resource.object_type.clone(),
+            resource_id: resource.object_id.clone(),
+            permission: permission.to_owned(),
+            context_hash: hasher.finish(),
+        }
+    }
+}
+
+enum Lookup {
+    Hit(bool),
+    /// Generation of the resource type when the lookup missed, see `CacheState::generations`
+    Miss(u64),
+}
+
+#[derive(Debug)]
+struct Entry {
+    allowed: bool,
+    expires_at: Instant,
+    seq: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<CacheKey, Entry>,
+    /// Keys in insertion order with the `seq` of their entry, keys that were evicted or
+    /// replaced since are skipped when evicting the oldest entry.
+    order: VecDeque<(u64, CacheKey)>,
+    next_seq: u64,
+    /// Bumped on every change to a resource type, a check that was sent before a change isn't
+    /// cached once it completes after it.
+    generations: HashMap<String, u64>,
+    /// Bumped when the whole cache is cleared, counting as a change to every resource type
+    clears: u64,
+    invalidations: u64,
+    watching: bool,
+}
+
+impl CacheState {
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+        self.clears += 1;
+    }
+
+    /// Both counters only grow, so the sum changes whenever either of them does
+    fn generation(&self, resource_type: &str) -> u64 {
+        self.clears + self.generations.get(resource_type).copied().unwrap_or(0)
+    }
+
+    /// Checks sent before the watch started may have missed changes it won't report, so they
+    /// aren't cached either.
+    fn set_watching(&mut self, watching: bool) {
+        self.watching = watching;
+        self.clear();
+    }
+
+    fn invalidate(&mut self, resource_type: &str) {
+        *self
+            .generations
+            .entry(resource_type.to_owned())
+            .or_default() += 1;
+        let before = self.entries.len();
+        self.entries
+            .retain(|key, _| key.resource_type != resource_type);
+        self.invalidations += (before - self.entries.len()) as u64;
+    }
+
+    fn evict_oldest(&mut self) {
+        while let Some((seq, key)) = self.order.pop_front() {
+            if self.entries.get(&key).is_some_and(|entry| entry.seq == seq) {
+                self.entries.remove(&key);
+                return;
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Cache {
+    ttl: Duration,
+    max_entries: usize,
+    state: Mutex<CacheState>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl Cache {
+    fn get(&self, key: &CacheKey) -> Lookup {
+        let state = self.state.lock().unwrap();
+        match state.entries.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Lookup::Hit(entry.allowed)
+            }
+            _ => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                Lookup::Miss(state.generation(&key.resource_type))
+            }
+        }
+    }
+
+    fn insert(&self, key: CacheKey, allowed: bool, generation: u64) {
+        let mut state = self.state.lock().unwrap();
+        if self.max_entries == 0
+            || !state.watching
+            || state.generation(&key.resource_type) != generation
+        {
+            return;
+        }
+        if !state.entries.contains_key(&key) && state.entries.len() >= self.max_entries {
+            let now = Instant::now();
+            state.entries.retain(|_, entry| entry.expires_at > now);
+            while state.entries.len() >= self.max_entries {
+                state.evict_oldest();
+            }
+        }
+        // Drop the keys of evicted entries before the queue outgrows the cache
+        if state.order.len() >= self.max_entries.saturating_mul(2) {
+            let CacheState { entries, order, .. } = &mut *state;
+            order.retain(|(seq, key)| entries.get(key).is_some_and(|entry| entry.seq == *seq));
+        }
+        let seq = state.next_seq;
+        state.next_seq += 1;
+        state.order.push_back((seq, key.clone()));
+        state.entries.insert(
+            key,
+            Entry {
+                allowed,
+                expires_at: Instant::now() + self.ttl,
+                seq,
+            },
+        );
+    }
+}
+
+/// Aborts the watch once the last clone of the client is dropped
+#[derive(Debug)]
+struct WatchTask(JoinHandle<()>);
+
+impl Drop for WatchTask {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+async fn watch(client: SpiceDBClient, cache: Arc<Cache>) {
+    // Changes are watched from a revision read before anything is cached, so the ones made while
+    // the watch connects are still reported
+    let start = match client.read_schema().await {
+        Ok(schema) => schema.read_at,
+        Err(_) => return,
+    };
+    let mut watcher = client.watcher(InMemoryCheckpointStore::new());
+    watcher.with_start_cursor(start);
+    let stream = match watcher.start().await {
+        Ok(stream) => stream,
+        Err(_) => return,
+    };
+    cache.state.lock().unwrap().set_watching(true);
+    tokio::pin!(stream);
+    while let Some(resp) = stream.next().await {
+        match resp {
+            Ok(resp) => {
+                let mut state = cache.state.lock().unwrap();
+                for update in &resp.updates {
+                    state.invalidate(&update.relationship.resource.object_type);
+                }
+            }
+            Err(_) => break,
+        }
+    }
+    // The watcher gave up reconnecting or its stream ended, changes can't be tracked anymore
+    cache.state.lock().unwrap().set_watching(false);
+}
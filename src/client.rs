@@ -1,18 +1,47 @@
-use crate::grpc::{BearerTokenInterceptor, GrpcResult};
+use std::sync::Arc;
+
+use tokio::io::{AsyncRead, AsyncSeek, AsyncWrite};
+use tokio_stream::Stream;
+
+use crate::batch::CheckBatcher;
+use crate::client_builder::SpiceDBClientBuilder;
+use crate::experimental::{
+    BulkExportRelationshipsRequest, BulkImportRelationshipsRequest, DiffSchemaRequest,
+    ReflectSchemaRequest, SpiceDBExperimentalClient,
+};
+use crate::grpc::GrpcResult;
 use crate::permission::{
-    CheckPermissionRequest, DeleteRelationshipsRequest, LookupResourcesRequest,
+    resolve_consistency, CheckBulkPermissionsRequest, CheckPermissionRequest,
+    DeleteRelationshipsRequest, ExpandPermissionTreeRequest, LookupResourcesRequest,
     LookupSubjectsRequest, ReadRelationshipsRequest, SpiceDBPermissionClient,
     WriteRelationshipsRequest,
 };
-use crate::schema::SpiceDBSchemaClient;
-use crate::spicedb::wrappers::{Consistency, ReadSchemaResponse};
+use crate::schema::{
+    Schema, SchemaChangePlan, SchemaChangePolicy, SchemaDriftReport, SchemaRegistry,
+    SpiceDBSchemaClient,
+};
+use crate::session::{Session, TokenStore, ZedTokenStore};
+use crate::single_flight::SingleFlights;
+use crate::spicedb::wrappers::{
+    Consistency, ExpandPermissionTreeResponse, ReadSchemaResponse, Relationship,
+};
 use crate::spicedb::{self, object_reference};
-use crate::{Actor, Entity, Resource};
+use crate::watch::{CheckpointStore, SpiceDBWatchClient, WatchRequest, Watcher};
+use crate::{Actor, Entity, Permission, Resource};
+use crate::{Error, RetryPolicy};
 
 #[derive(Clone, Debug)]
 pub struct SpiceDBClient {
-    schema_service_client: SpiceDBSchemaClient,
-    permission_service_client: SpiceDBPermissionClient,
+    pub(crate) schema_service_client: SpiceDBSchemaClient,
+    pub(crate) permission_service_client: SpiceDBPermissionClient,
+    pub(crate) watch_service_client: SpiceDBWatchClient,
+    pub(crate) experimental_service_client: SpiceDBExperimentalClient,
+    pub(crate) retry_policy: RetryPolicy,
+    pub(crate) token_store: TokenStore,
+    pub(crate) session_key: Option<String>,
+    pub(crate) consistency: Option<Consistency>,
+    pub(crate) check_batcher: Option<Arc<CheckBatcher>>,
+    pub(crate) single_flight: Option<Arc<SingleFlights>>,
 }
 
 impl SpiceDBClient {
@@ -31,24 +60,52 @@ impl SpiceDBClient {
         Self::new(addr, token).await
     }
 
+    /// Connects with the default connection options, see `builder` to configure TLS, timeouts
+    /// and keepalive.
     pub async fn new(addr: impl Into<String>, token: impl AsRef<str>) -> anyhow::Result<Self> {
-        let token = format!("Bearer {}", token.as_ref()).parse()?;
-        let interceptor = BearerTokenInterceptor::new(token);
-        let channel = tonic::transport::Channel::from_shared(addr.into())?
-            .connect()
-            .await?;
-        Ok(SpiceDBClient {
-            schema_service_client:
-                spicedb::schema_service_client::SchemaServiceClient::with_interceptor(
-                    channel.clone(),
-                    interceptor.clone(),
-                ),
-            permission_service_client:
-                spicedb::permissions_service_client::PermissionsServiceClient::with_interceptor(
-                    channel,
-                    interceptor,
-                ),
-        })
+        Ok(Self::builder(addr, token.as_ref()).connect().await?)
+    }
+
+    pub fn builder(addr: impl Into<String>, token: impl Into<String>) -> SpiceDBClientBuilder {
+        SpiceDBClientBuilder::new(addr, token)
+    }
+
+    /// Copy of this client retrying idempotent requests with `policy`, see `RetryPolicy`.
+    pub fn with_retry_policy(&self, policy: RetryPolicy) -> Self {
+        let mut client = self.clone();
+        client.retry_policy = policy;
+        client
+    }
+
+    /// Copy of this client sending its reads with `consistency` unless they set one, e.g.
+    /// `client.with_consistency(Consistency::FullyConsistent).check_permission(..)`. Reads of a
+    /// session use the session's token instead.
+    pub fn with_consistency(&self, consistency: Consistency) -> Self {
+        let mut client = self.clone();
+        client.consistency = Some(consistency);
+        client
+    }
+
+    /// Copy of this client whose writes record their `ZedToken` under `key`, and whose reads
+    /// without an explicit consistency are at least as fresh as the last of those writes.
+    /// See the `session` module.
+    pub fn session(&self, key: impl Into<String>) -> Self {
+        let mut client = self.clone();
+        client.session_key = Some(key.into());
+        client
+    }
+
+    /// Copy of this client keeping the `ZedToken`s of its sessions in `store`.
+    pub fn with_token_store(&self, store: impl ZedTokenStore + 'static) -> Self {
+        let mut client = self.clone();
+        client.token_store = TokenStore::new(store);
+        client
+    }
+
+    fn current_session(&self) -> Option<Session> {
+        self.session_key
+            .clone()
+            .map(|key| Session::new(self.token_store.clone(), key))
     }
 
     pub fn leak(self) -> &'static Self {
@@ -63,33 +120,86 @@ impl SpiceDBClient {
         self.permission_service_client.clone()
     }
 
+    pub fn watch_service_client(&self) -> SpiceDBWatchClient {
+        self.watch_service_client.clone()
+    }
+
+    pub fn experimental_service_client(&self) -> SpiceDBExperimentalClient {
+        self.experimental_service_client.clone()
+    }
+
     pub fn create_relationships_request(&self) -> WriteRelationshipsRequest {
-        WriteRelationshipsRequest::new(self.permission_service_client())
+        let mut request = WriteRelationshipsRequest::new(self.permission_service_client());
+        request
+            .retry_policy(self.retry_policy.clone())
+            .session(self.current_session());
+        request
     }
 
     pub fn delete_relationships_request<R>(&self) -> DeleteRelationshipsRequest<R>
     where
         R: Resource,
     {
-        DeleteRelationshipsRequest::new(self.permission_service_client())
+        let mut request = DeleteRelationshipsRequest::new(self.permission_service_client());
+        request
+            .retry_policy(self.retry_policy.clone())
+            .session(self.current_session());
+        request
     }
 
     pub fn read_relationships_request(&self) -> ReadRelationshipsRequest {
-        ReadRelationshipsRequest::new(self.permission_service_client())
+        let mut request = ReadRelationshipsRequest::new(self.permission_service_client());
+        request
+            .retry_policy(self.retry_policy.clone())
+            .session(self.current_session())
+            .default_consistency(self.consistency.clone());
+        request
     }
 
     pub fn check_permission_request<R>(&self) -> CheckPermissionRequest<R>
     where
         R: Resource,
     {
-        CheckPermissionRequest::new(self.permission_service_client())
+        let mut request = CheckPermissionRequest::new(self.permission_service_client());
+        request
+            .retry_policy(self.retry_policy.clone())
+            .session(self.current_session())
+            .default_consistency(self.consistency.clone())
+            .single_flight(self.single_flight.clone());
+        request
+    }
+
+    pub fn check_bulk_permissions_request(&self) -> CheckBulkPermissionsRequest {
+        let mut request = CheckBulkPermissionsRequest::new(self.permission_service_client());
+        request
+            .retry_policy(self.retry_policy.clone())
+            .session(self.current_session())
+            .default_consistency(self.consistency.clone());
+        request
+    }
+
+    pub fn expand_permission_tree_request<R>(&self) -> ExpandPermissionTreeRequest<R>
+    where
+        R: Resource,
+    {
+        let mut request = ExpandPermissionTreeRequest::new(self.permission_service_client());
+        request
+            .retry_policy(self.retry_policy.clone())
+            .session(self.current_session())
+            .default_consistency(self.consistency.clone());
+        request
     }
 
     pub fn lookup_resources_request<R>(&self) -> LookupResourcesRequest<R>
     where
         R: Resource,
     {
-        LookupResourcesRequest::new(self.permission_service_client())
+        let mut request = LookupResourcesRequest::new(self.permission_service_client());
+        request
+            .retry_policy(self.retry_policy.clone())
+            .session(self.current_session())
+            .default_consistency(self.consistency.clone());
+        request
     }
 
     pub fn lookup_subjects_request<S, R>(&self) -> LookupSubjectsRequest<S, R>
@@ -97,7 +207,46 @@ impl SpiceDBClient {
         S: Entity,
         R: Resource,
     {
-        LookupSubjectsRequest::new(self.permission_service_client())
+        let mut request = LookupSubjectsRequest::new(self.permission_service_client());
+        request
+            .retry_policy(self.retry_policy.clone())
+            .session(self.current_session())
+            .default_consistency(self.consistency.clone())
+            .single_flight(self.single_flight.clone());
+        request
+    }
+
+    pub fn bulk_import_relationships_request(&self) -> BulkImportRelationshipsRequest {
+        BulkImportRelationshipsRequest::new(self.experimental_service_client())
+    }
+
+    pub fn bulk_export_relationships_request(&self) -> BulkExportRelationshipsRequest {
+        let mut request = BulkExportRelationshipsRequest::new(self.experimental_service_client());
+        request
+            .session(self.current_session())
+            .default_consistency(self.consistency.clone());
+        request
+    }
+
+    pub fn reflect_schema_request(&self) -> ReflectSchemaRequest {
+        ReflectSchemaRequest::new(self.experimental_service_client())
+    }
+
+    pub fn diff_schema_request(&self, schema: impl Into<String>) -> DiffSchemaRequest {
+        DiffSchemaRequest::new(self.experimental_service_client(), schema.into())
+    }
+
+    pub fn watch_request(&self) -> WatchRequest {
+        WatchRequest::new(self.watch_service_client())
+    }
+
+    /// Resumable watch that reconnects on transient failures and checkpoints its progress in
+    /// `store`.
+    pub fn watcher<S>(&self, store: S) -> Watcher<S>
+    where
+        S: CheckpointStore + 'static,
+    {
+        Watcher::new(self.watch_request(), store)
     }
 
     pub async fn delete_relationships<R>(
@@ -141,6 +290,31 @@ impl SpiceDBClient {
         request.send().await
     }
 
+    /// Imports all `relationships` in chunks of the default size and returns the number of
+    /// relationships loaded. Accepts raw `spicedb::Relationship`s as well as
+    /// `wrappers::Relationship`s.
+    pub async fn bulk_import_relationships<I>(&self, relationships: I) -> GrpcResult<u64>
+    where
+        I: IntoIterator,
+        I::Item: Into<spicedb::Relationship> + Send,
+        I::IntoIter: Send + 'static,
+    {
+        self.bulk_import_relationships_request()
+            .send(relationships)
+            .await
+    }
+
+    /// Exports every relationship in the system, use `bulk_export_relationships_request` to resume
+    /// an interrupted export from a cursor.
+    pub async fn bulk_export_relationships(
+        &self,
+        consistency: Consistency,
+    ) -> GrpcResult<impl Stream<Item = GrpcResult<Relationship>>> {
+        let mut request = self.bulk_export_relationships_request();
+        request.with_consistency(consistency);
+        request.send_relationships_stream().await
+    }
+
     /// Shortcut for the most common use case of looking up resources, to quickly collect all ID's
     /// returned in one call.
     pub async fn lookup_resources<R>(
@@ -214,6 +388,11 @@ impl SpiceDBClient {
     where
         R: Resource,
     {
+        if let Some(batcher) = &self.check_batcher {
+            return self
+                .batched_check::<R>(batcher, actor, resource_id.into(), permission, None)
+                .await;
+        }
         let mut request = self.check_permission_request::<R>();
         request.subject(actor.to_subject());
         request.resource(object_reference::<R>(resource_id.into()));
@@ -233,33 +412,151 @@ impl SpiceDBClient {
     where
         R: Resource,
     {
+        let consistency = Consistency::AtLeastAsFresh(token);
+        if let Some(batcher) = &self.check_batcher {
+            return self
+                .batched_check::<R>(
+                    batcher,
+                    actor,
+                    resource_id.into(),
+                    permission,
+                    Some(consistency),
+                )
+                .await;
+        }
         let mut request = self.check_permission_request::<R>();
         request.subject(actor.to_subject());
         request.resource(object_reference::<R>(resource_id.into()));
         request.permission(permission);
-        request.consistency(Consistency::AtLeastAsFresh(token));
+        request.consistency(consistency);
         let resp = request.send().await?;
         Ok(resp.permissionship
             == spicedb::check_permission_response::Permissionship::HasPermission as i32)
     }
 
-    pub async fn write_schema(&self, schema: String) -> Result<spicedb::ZedToken, tonic::Status> {
+    async fn batched_check<R>(
+        &self,
+        batcher: &Arc<CheckBatcher>,
+        actor: &impl Actor,
+        resource_id: R::Id,
+        permission: R::Permissions,
+        consistency: Option<Consistency>,
+    ) -> GrpcResult<bool>
+    where
+        R: Resource,
+    {
+        let mut consistency = consistency.map(Into::into);
+        resolve_consistency(
+            &mut consistency,
+            self.current_session().as_ref(),
+            self.consistency.clone(),
+        )
+        .await?;
+        let item = spicedb::CheckBulkPermissionsRequestItem {
+            resource: Some(object_reference::<R>(resource_id)),
+            permission: permission.name().to_owned(),
+            subject: Some(actor.to_subject()),
+            context: None,
+        };
+        let permissionship = batcher
+            .check(item, consistency, self.retry_policy.clone())
+            .await?;
+        Ok(permissionship.has_permission())
+    }
+
+    /// Expands `permission` on the resource into the tree of relations and subjects granting it,
+    /// useful to explain who has access and why.
+    pub async fn expand_permission_tree<R>(
+        &self,
+        id: impl Into<R::Id>,
+        permission: R::Permissions,
+    ) -> GrpcResult<ExpandPermissionTreeResponse>
+    where
+        R: Resource,
+    {
+        let mut request = self.expand_permission_tree_request::<R>();
+        request.resource(id, permission);
+        request.send().await
+    }
+
+    pub async fn write_schema(&self, schema: String) -> GrpcResult<spicedb::ZedToken> {
         let resp = self
             .schema_service_client()
             .write_schema(spicedb::WriteSchemaRequest { schema })
             .await?
             .into_inner();
         resp.written_at
-            .ok_or_else(|| tonic::Status::internal("ZedToken expected"))
+            .ok_or_else(|| Error::invalid_response("ZedToken expected"))
     }
 
-    pub async fn read_schema(&self) -> Result<ReadSchemaResponse, tonic::Status> {
+    pub async fn read_schema(&self) -> GrpcResult<ReadSchemaResponse> {
         let resp = self
-            .schema_service_client()
-            .read_schema(spicedb::ReadSchemaRequest {})
+            .retry_policy
+            .run("ReadSchema", || {
+                let mut client = self.schema_service_client();
+                async move { client.read_schema(spicedb::ReadSchemaRequest {}).await }
+            })
             .await?
             .into_inner()
-            .into();
+            .try_into()?;
         Ok(resp)
     }
+
+    /// Reflects the deployed schema into an owned `Schema` model with lookup helpers.
+    pub async fn reflect_schema(&self) -> GrpcResult<Schema> {
+        self.reflect_schema_request().send().await
+    }
+
+    /// Verifies the types registered in `registry` against the deployed schema, meant to be
+    /// called on startup to fail fast when the schema and the rust types drifted apart.
+    pub async fn verify_schema(&self, registry: &SchemaRegistry) -> GrpcResult<SchemaDriftReport> {
+        let schema = self.reflect_schema().await?;
+        Ok(registry.verify(&schema))
+    }
+
+    /// Computes the changes `schema` would make to the deployed schema, without writing it.
+    pub async fn plan_schema_change(
+        &self,
+        schema: impl Into<String>,
+    ) -> GrpcResult<SchemaChangePlan> {
+        let mut request = self.diff_schema_request(schema);
+        request.with_consistency(Consistency::FullyConsistent);
+        request.send().await
+    }
+
+    /// Writes the schema of `plan` if `policy` allows all of its destructive changes, fails with
+    /// `Error::SchemaChangeRefused` otherwise. Fails with `Error::StaleSchemaChangePlan` if the
+    /// deployed schema changed since the plan was made.
+    pub async fn apply_schema_change(
+        &self,
+        plan: &SchemaChangePlan,
+        policy: SchemaChangePolicy,
+    ) -> GrpcResult<spicedb::ZedToken> {
+        let current = self.plan_schema_change(plan.schema.clone()).await?;
+        if current.changes != plan.changes {
+            return Err(Error::StaleSchemaChangePlan);
+        }
+        crate::schema::change::check_policy(self, plan, policy).await?;
+        self.write_schema(plan.schema.clone()).await
+    }
+
+    /// Writes a backup of the schema and all relationships to `writer`, returns the `ZedToken` of
+    /// the snapshot that was backed up. The file format is specific to this crate, see
+    /// `restore`.
+    pub async fn backup<W>(&self, writer: &mut W) -> anyhow::Result<spicedb::ZedToken>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        crate::backup::backup(self, writer).await
+    }
+
+    /// Restores a backup written by `backup`, overwriting the schema and importing all
+    /// relationships. Returns the number of relationships loaded. The whole file is checked
+    /// before the schema is written, a truncated or corrupt backup leaves SpiceDB untouched.
+    pub async fn restore<R>(&self, reader: &mut R) -> anyhow::Result<u64>
+    where
+        R: AsyncRead + AsyncSeek + Unpin,
+    {
+        crate::backup::restore(self, reader, 1_000).await
+    }
 }
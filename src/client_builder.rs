@@ -0,0 +1,281 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tonic::transport::{Channel, Endpoint};
+
+use crate::batch::CheckBatcher;
+use crate::grpc::{BearerTokenInterceptor, GrpcResult};
+use crate::session::{TokenStore, ZedTokenStore};
+use crate::single_flight::SingleFlights;
+use crate::spicedb::wrappers::Consistency;
+use crate::{spicedb, Error, RetryPolicy, SpiceDBClient};
+
+/// Configures the connection of a `SpiceDBClient`, created by `SpiceDBClient::builder`.
+///
+/// ```ignore
+/// let client = SpiceDBClient::builder("https://spicedb.internal:50051", token)
+///     .tls_ca_certificate(std::fs::read("ca.pem")?)
+///     .connect_timeout(Duration::from_secs(5))
+///     .timeout(Duration::from_secs(10))
+///     .connect()
+///     .await?;
+/// ```
+#[derive(Clone)]
+pub struct SpiceDBClientBuilder {
+    addr: String,
+    token: String,
+    #[cfg(feature = "tls")]
+    tls: Option<tonic::transport::ClientTlsConfig>,
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    keepalive_interval: Option<Duration>,
+    keepalive_timeout: Option<Duration>,
+    tcp_nodelay: bool,
+    user_agent: Option<String>,
+    lazy: bool,
+    retry_policy: RetryPolicy,
+    token_store: TokenStore,
+    consistency: Option<Consistency>,
+    check_batching: Option<(Duration, usize)>,
+    single_flight: bool,
+}
+
+impl SpiceDBClientBuilder {
+    pub(crate) fn new(addr: impl Into<String>, token: impl Into<String>) -> Self {
+        SpiceDBClientBuilder {
+            addr: addr.into(),
+            token: token.into(),
+            #[cfg(feature = "tls")]
+            tls: None,
+            connect_timeout: None,
+            timeout: None,
+            keepalive_interval: None,
+            keepalive_timeout: None,
+            tcp_nodelay: true,
+            user_agent: None,
+            lazy: false,
+            retry_policy: RetryPolicy::never(),
+            token_store: TokenStore::default(),
+            consistency: None,
+            check_batching: None,
+            single_flight: false,
+        }
+    }
+
+    /// Uses TLS with the system's root certificates, implied by the other `tls_*` options.
+    #[cfg(feature = "tls")]
+    pub fn tls(&mut self) -> &mut Self {
+        self.tls.get_or_insert_with(Default::default);
+        self
+    }
+
+    /// Trusts the PEM encoded CA certificate, e.g. of a self-signed SpiceDB deployment.
+    #[cfg(feature = "tls")]
+    pub fn tls_ca_certificate(&mut self, pem: impl AsRef<[u8]>) -> &mut Self {
+        let certificate = tonic::transport::Certificate::from_pem(pem);
+        self.update_tls(|tls| tls.ca_certificate(certificate))
+    }
+
+    /// Authenticates with a PEM encoded client certificate and key, for mTLS.
+    #[cfg(feature = "tls")]
+    pub fn tls_client_identity(
+        &mut self,
+        cert_pem: impl AsRef<[u8]>,
+        key_pem: impl AsRef<[u8]>,
+    ) -> &mut Self {
+        let identity = tonic::transport::Identity::from_pem(cert_pem, key_pem);
+        self.update_tls(|tls| tls.identity(identity))
+    }
+
+    /// Verifies the server certificate against `domain_name` instead of the host of the address.
+    #[cfg(feature = "tls")]
+    pub fn tls_domain_name(&mut self, domain_name: impl Into<String>) -> &mut Self {
+        let domain_name = domain_name.into();
+        self.update_tls(|tls| tls.domain_name(domain_name))
+    }
+
+    #[cfg(feature = "tls")]
+    fn update_tls(
+        &mut self,
+        f: impl FnOnce(tonic::transport::ClientTlsConfig) -> tonic::transport::ClientTlsConfig,
+    ) -> &mut Self {
+        self.tls = Some(f(self.tls.take().unwrap_or_default()));
+        self
+    }
+
+    pub fn connect_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Deadline of every request, a request exceeding it fails with `DeadlineExceeded`. Streams
+    /// like `Watch` are cut off as well, so leave it unset for long running watches.
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sends HTTP/2 pings at `interval` to keep the connection alive through proxies and load
+    /// balancers that drop idle connections.
+    pub fn keepalive_interval(&mut self, interval: Duration) -> &mut Self {
+        self.keepalive_interval = Some(interval);
+        self
+    }
+
+    /// How long to wait for the reply to a keepalive ping before closing the connection.
+    pub fn keepalive_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.keepalive_timeout = Some(timeout);
+        self
+    }
+
+    /// Enabled by default.
+    pub fn tcp_nodelay(&mut self, enabled: bool) -> &mut Self {
+        self.tcp_nodelay = enabled;
+        self
+    }
+
+    pub fn user_agent(&mut self, user_agent: impl Into<String>) -> &mut Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Defers connecting to the first request, so `connect` succeeds even if SpiceDB isn't
+    /// reachable yet.
+    pub fn lazy(&mut self, lazy: bool) -> &mut Self {
+        self.lazy = lazy;
+        self
+    }
+
+    /// Retries idempotent requests failing with a transient error, no retries by default.
+    pub fn retry_policy(&mut self, policy: RetryPolicy) -> &mut Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Where sessions keep their `ZedToken`s, in memory by default. See `SpiceDBClient::session`.
+    pub fn token_store(&mut self, store: impl ZedTokenStore + 'static) -> &mut Self {
+        self.token_store = TokenStore::new(store);
+        self
+    }
+
+    /// Consistency of reads that don't set one, SpiceDB's default (`MinimizeLatency`) if unset.
+    pub fn consistency(&mut self, consistency: Consistency) -> &mut Self {
+        self.consistency = Some(consistency);
+        self
+    }
+
+    /// Sends the checks of concurrent `check_permission` calls together in one
+    /// `CheckBulkPermissions`, waiting up to `window` for more checks after the first one or
+    /// until there are `max_batch_size`. Keep `max_batch_size` within the server's limit of
+    /// checks per request. Only checks with the same consistency and retry policy, e.g. from
+    /// `with_retry_policy` copies, share a batch.
+    pub fn check_batching(&mut self, window: Duration, max_batch_size: usize) -> &mut Self {
+        self.check_batching = Some((window, max_batch_size));
+        self
+    }
+
+    /// Lets concurrent identical `CheckPermission` requests and `LookupSubjects` requests sent
+    /// with `send_collect_ids` share one call and its result. Requests are identical if their
+    /// messages, including the consistency, are.
+    pub fn single_flight(&mut self, enabled: bool) -> &mut Self {
+        self.single_flight = enabled;
+        self
+    }
+
+    pub async fn connect(&self) -> GrpcResult<SpiceDBClient> {
+        let token = format!("Bearer {}", self.token)
+            .parse()
+            .map_err(|_| Error::invalid_request("token is not a valid header value"))?;
+        let endpoint = self.endpoint()?;
+        let channel = if self.lazy {
+            endpoint.connect_lazy()
+        } else {
+            endpoint.connect().await?
+        };
+        Ok(client(
+            channel,
+            BearerTokenInterceptor::new(token),
+            self.retry_policy.clone(),
+            self.token_store.clone(),
+            self.consistency.clone(),
+            self.check_batching,
+            self.single_flight,
+        ))
+    }
+
+    fn endpoint(&self) -> GrpcResult<Endpoint> {
+        let mut endpoint = Endpoint::from_shared(self.addr.clone())
+            .map_err(|e| Error::invalid_request(format!("invalid address: {}", e)))?
+            .tcp_nodelay(self.tcp_nodelay);
+        #[cfg(feature = "tls")]
+        if let Some(tls) = self.tls.clone() {
+            endpoint = endpoint
+                .tls_config(tls)
+                .map_err(|e| Error::invalid_request(format!("invalid TLS config: {}", e)))?;
+        }
+        if let Some(timeout) = self.connect_timeout {
+            endpoint = endpoint.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.timeout {
+            endpoint = endpoint.timeout(timeout);
+        }
+        if let Some(interval) = self.keepalive_interval {
+            endpoint = endpoint.http2_keep_alive_interval(interval);
+        }
+        if let Some(timeout) = self.keepalive_timeout {
+            endpoint = endpoint.keep_alive_timeout(timeout);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            endpoint = endpoint
+                .user_agent(user_agent.as_str())
+                .map_err(|_| Error::invalid_request("user agent is not a valid header value"))?;
+        }
+        Ok(endpoint)
+    }
+}
+
+fn client(
+    channel: Channel,
+    interceptor: BearerTokenInterceptor,
+    retry_policy: RetryPolicy,
+    token_store: TokenStore,
+    consistency: Option<Consistency>,
+    check_batching: Option<(Duration, usize)>,
+    single_flight: bool,
+) -> SpiceDBClient {
+    let permission_service_client =
+        spicedb::permissions_service_client::PermissionsServiceClient::with_interceptor(
+            channel.clone(),
+            interceptor.clone(),
+        );
+    let check_batcher = check_batching.map(|(window, max_batch_size)| {
+        Arc::new(CheckBatcher::new(
+            permission_service_client.clone(),
+            window,
+            max_batch_size,
+        ))
+    });
+    SpiceDBClient {
+        schema_service_client:
+            spicedb::schema_service_client::SchemaServiceClient::with_interceptor(
+                channel.clone(),
+                interceptor.clone(),
+            ),
+        permission_service_client,
+        watch_service_client: spicedb::watch_service_client::WatchServiceClient::with_interceptor(
+            channel.clone(),
+            interceptor.clone(),
+        ),
+        experimental_service_client:
+            spicedb::experimental_service_client::ExperimentalServiceClient::with_interceptor(
+                channel,
+                interceptor,
+            ),
+        retry_policy,
+        token_store,
+        session_key: None,
+        consistency,
+        check_batcher,
+        single_flight: single_flight.then(|| Arc::new(SingleFlights::default())),
+    }
+}
@@ -0,0 +1,122 @@
+//! Generates the `Entity`, `Resource` and `Caveat` types of this crate from a SpiceDB schema,
+//! so the schema becomes the single source of truth for them.
+//!
+//! For every definition a unit struct with its `Entity` impl and a `Relations` enum is
+//! generated, definitions with relations or permissions also get a `Resource` impl, with a
+//! `Permissions` enum or `NoPermissions` if they only have relations.
+//! Every caveat gets a unit struct implementing `Caveat` and a context struct with one optional
+//! field per parameter.
+//!
+//! Generated code refers to `spicedb_rust` by path and needs no other dependencies.
+//! ```rust,ignore
+//! // build.rs
+//! fn main() {
+//!     spicedb_rust::codegen::generate("schema.zed").unwrap();
+//! }
+//!
+//! // lib.rs
+//! include!(concat!(env!("OUT_DIR"), "/schema.rs"));
+//! ```
+//!
+//! Teams that don't own the schema file can generate the types from the schema deployed in
+//! SpiceDB instead with `Codegen::generate_from_env`, or the `spicedb-codegen` binary of the
+//! `cli` feature.
+
+mod render;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::Context;
+
+use crate::schema::Schema;
+use crate::SpiceDBClient;
+
+/// Generates the types for the `.zed` schema at `schema_path` into `$OUT_DIR/<file stem>.rs`
+/// with `String` ids, meant to be called from a build script.
+pub fn generate(schema_path: impl AsRef<Path>) -> anyhow::Result<()> {
+    Codegen::new().generate(schema_path)
+}
+
+#[derive(Clone, Debug)]
+pub struct Codegen {
+    default_id_type: String,
+    id_types: BTreeMap<String, String>,
+}
+
+impl Default for Codegen {
+    fn default() -> Self {
+        Codegen {
+            default_id_type: "String".to_owned(),
+            id_types: BTreeMap::new(),
+        }
+    }
+}
+
+impl Codegen {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rust type used as `Entity::Id` for all definitions without an override, `String` by
+    /// default.
+    pub fn default_id_type(&mut self, id_type: impl Into<String>) -> &mut Self {
+        self.default_id_type = id_type.into();
+        self
+    }
+
+    /// Rust type used as `Entity::Id` of the definition named `object_type`, e.g.
+    /// `id_type("user", "uuid::Uuid")`.
+    pub fn id_type(
+        &mut self,
+        object_type: impl Into<String>,
+        id_type: impl Into<String>,
+    ) -> &mut Self {
+        self.id_types.insert(object_type.into(), id_type.into());
+        self
+    }
+
+    /// Generates the types for the `.zed` schema at `schema_path` into
+    /// `$OUT_DIR/<file stem>.rs`, meant to be called from a build script.
+    pub fn generate(&self, schema_path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let schema_path = schema_path.as_ref();
+        println!("cargo:rerun-if-changed={}", schema_path.display());
+        let out_dir = std::env::var("OUT_DIR").context("OUT_DIR is only set in build scripts")?;
+        let file_name = schema_path
+            .file_stem()
+            .context("Schema path has no file name")?;
+        let schema = std::fs::read_to_string(schema_path)
+            .with_context(|| format!("Could not read {}", schema_path.display()))?;
+        let code = self.render_zed(&schema)?;
+        let out_path = Path::new(&out_dir).join(file_name).with_extension("rs");
+        std::fs::write(out_path, code)?;
+        Ok(())
+    }
+
+    /// Connects with `SpiceDBClient::from_env`, reflects the deployed schema and writes its types
+    /// to `out_path`.
+    pub async fn generate_from_env(&self, out_path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let client = SpiceDBClient::from_env().await?;
+        let code = self.render_from_client(&client).await?;
+        std::fs::write(out_path, code)?;
+        Ok(())
+    }
+
+    /// Renders the types for the schema currently deployed in SpiceDB.
+    pub async fn render_from_client(&self, client: &SpiceDBClient) -> anyhow::Result<String> {
+        let schema = client.reflect_schema().await?;
+        self.render(&schema)
+    }
+
+    /// Renders the types for the text of a `.zed` schema.
+    pub fn render_zed(&self, schema: &str) -> anyhow::Result<String> {
+        let schema = crate::schema::parse_schema(schema)?.into();
+        self.render(&schema)
+    }
+
+    /// Renders the types for a schema model, e.g. one returned by `reflect_schema`. Fails if two
+    /// names of the schema map to the same rust name.
+    pub fn render(&self, schema: &Schema) -> anyhow::Result<String> {
+        render::render(self, schema)
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use anyhow::bail;
+
+use crate::codegen::Codegen;
+use crate::schema::{CaveatDefinition, Definition, Schema};
+
+/// Strict and reserved keywords of all editions, they can't be used as identifiers.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Types the generated code uses without a path, generated types can't shadow them.
+const PRELUDE_TYPES: &[&str] = &["From", "Option", "String", "Vec"];
+
+/// `org/team_member` -> `OrgTeamMember`
+fn pascal_case(name: &str) -> String {
+    name.split(['/', '_'])
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Appends `_` to keywords, raw identifiers like `r#self` aren't allowed for all of them.
+fn escape(name: String) -> String {
+    if KEYWORDS.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+/// Name of the struct of a definition or caveat, and of the variant of a relation or permission.
+fn type_name(name: &str) -> String {
+    escape(pascal_case(name))
+}
+
+fn field_name(name: &str) -> String {
+    escape(name.to_owned())
+}
+
+fn relations_enum(definition: &Definition) -> Option<String> {
+    (!definition.relations.is_empty()).then(|| format!("{}Relation", pascal_case(&definition.name)))
+}
+
+fn permissions_enum(definition: &Definition) -> Option<String> {
+    (!definition.permissions.is_empty())
+        .then(|| format!("{}Permission", pascal_case(&definition.name)))
+}
+
+fn context_struct(caveat: &CaveatDefinition) -> String {
+    format!("{}Context", pascal_case(&caveat.name))
+}
+
+/// Records that `source` generates `name`, failing if something else already generates it.
+fn claim(names: &mut BTreeMap<String, String>, name: String, source: String) -> anyhow::Result<()> {
+    if PRELUDE_TYPES.contains(&name.as_str()) {
+        bail!(
+            "{} generates `{}`, which shadows the prelude type",
+            source,
+            name
+        );
+    }
+    if let Some(previous) = names.get(&name) {
+        bail!("{} and {} both generate `{}`", previous, source, name);
+    }
+    names.insert(name, source);
+    Ok(())
+}
+
+/// Fails if two items of `schema` map to the same rust name, e.g. the definitions `org/team` and
+/// `org_team`, or the definition `document_relation` and the relations of `document`.
+fn check_names(schema: &Schema) -> anyhow::Result<()> {
+    let mut types = BTreeMap::new();
+    for definition in &schema.definitions {
+        let source = format!("definition `{}`", definition.name);
+        claim(&mut types, type_name(&definition.name), source.clone())?;
+        let enums = [relations_enum(definition), permissions_enum(definition)];
+        for name in enums.into_iter().flatten() {
+            claim(&mut types, name, source.clone())?;
+        }
+        let mut variants = BTreeMap::new();
+        for relation in &definition.relations {
+            let source = format!("relation `{}#{}`", definition.name, relation.name);
+            claim(&mut variants, type_name(&relation.name), source)?;
+        }
+        let mut variants = BTreeMap::new();
+        for permission in &definition.permissions {
+            let source = format!("permission `{}#{}`", definition.name, permission.name);
+            claim(&mut variants, type_name(&permission.name), source)?;
+        }
+    }
+    for caveat in &schema.caveats {
+        let source = format!("caveat `{}`", caveat.name);
+        claim(&mut types, type_name(&caveat.name), source.clone())?;
+        claim(&mut types, context_struct(caveat), source)?;
+        let mut fields = BTreeMap::new();
+        for parameter in &caveat.parameters {
+            let source = format!("parameter `{}` of caveat `{}`", parameter.name, caveat.name);
+            claim(&mut fields, field_name(&parameter.name), source)?;
+        }
+    }
+    Ok(())
+}
+
+/// Maps a CEL parameter type like `list<int>` to the rust type of the context field.
+fn rust_type(parameter_type: &str) -> String {
+    let parameter_type = parameter_type.trim();
+    if let Some(inner) = parameter_type
+        .strip_prefix("list<")
+        .and_then(|t| t.strip_suffix('>'))
+    {
+        return format!("Vec<{}>", rust_type(inner));
+    }
+    if let Some(inner) = parameter_type
+        .strip_prefix("map<")
+        .and_then(|t| t.strip_suffix('>'))
+    {
+        return format!("std::collections::HashMap<String, {}>", rust_type(inner));
+    }
+    match parameter_type {
+        "int" => "i64",
+        "uint" => "u64",
+        "double" => "f64",
+        "bool" => "bool",
+        "string" | "bytes" | "duration" | "timestamp" | "ipaddress" => "String",
+        _ => "spicedb_rust::prost_types::Value",
+    }
+    .to_owned()
+}
+
+/// Renders an enum of `names` with conversions into `&'static str` and `IntoEnumIterator`.
+fn render_enum(out: &mut String, enum_name: &str, names: &[&str]) {
+    let variants = names.iter().map(|n| type_name(n)).collect::<Vec<_>>();
+    writeln!(out, "#[derive(Clone, Copy, Debug, PartialEq, Eq)]").unwrap();
+    writeln!(out, "pub enum {} {{", enum_name).unwrap();
+    for variant in &variants {
+        writeln!(out, "    {},", variant).unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+
+    writeln!(out, "impl From<{}> for &'static str {{", enum_name).unwrap();
+    writeln!(out, "    fn from(value: {}) -> Self {{", enum_name).unwrap();
+    writeln!(out, "        match value {{").unwrap();
+    for (variant, name) in variants.iter().zip(names) {
+        writeln!(
+            out,
+            "            {}::{} => \"{}\",",
+            enum_name, variant, name
+        )
+        .unwrap();
+    }
+    writeln!(out, "        }}\n    }}\n}}\n").unwrap();
+
+    writeln!(
+        out,
+        "impl spicedb_rust::IntoEnumIterator for {} {{",
+        enum_name
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "    type Iterator = std::array::IntoIter<Self, {}>;\n",
+        variants.len()
+    )
+    .unwrap();
+    writeln!(out, "    fn iter() -> Self::Iterator {{").unwrap();
+    writeln!(out, "        [").unwrap();
+    for variant in &variants {
+        writeln!(out, "            {}::{},", enum_name, variant).unwrap();
+    }
+    writeln!(out, "        ]\n        .into_iter()\n    }}\n}}\n").unwrap();
+}
+
+fn render_definition(codegen: &Codegen, out: &mut String, definition: &Definition) {
+    let name = type_name(&definition.name);
+    let relations =
+        relations_enum(definition).unwrap_or_else(|| "spicedb_rust::NoRelations".to_owned());
+    let id_type = codegen
+        .id_types
+        .get(&definition.name)
+        .unwrap_or(&codegen.default_id_type);
+
+    writeln!(out, "pub struct {};\n", name).unwrap();
+    writeln!(out, "impl spicedb_rust::Entity for {} {{", name).unwrap();
+    writeln!(out, "    type Relations = {};", relations).unwrap();
+    writeln!(out, "    type Id = {};\n", id_type).unwrap();
+    writeln!(out, "    fn object_type() -> &'static str {{").unwrap();
+    writeln!(out, "        \"{}\"\n    }}\n}}\n", definition.name).unwrap();
+
+    if !definition.relations.is_empty() {
+        let names = definition
+            .relations
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect::<Vec<_>>();
+        render_enum(out, &relations, &names);
+    }
+    // Definitions with relations are the resource of relationships even without permissions
+    if definition.relations.is_empty() && definition.permissions.is_empty() {
+        return;
+    }
+    let permissions =
+        permissions_enum(definition).unwrap_or_else(|| "spicedb_rust::NoPermissions".to_owned());
+    writeln!(out, "impl spicedb_rust::Resource for {} {{", name).unwrap();
+    writeln!(out, "    type Permissions = {};\n}}\n", permissions).unwrap();
+    if !definition.permissions.is_empty() {
+        let names = definition
+            .permissions
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect::<Vec<_>>();
+        render_enum(out, &permissions, &names);
+    }
+}
+
+fn render_caveat(out: &mut String, caveat: &CaveatDefinition) {
+    let name = type_name(&caveat.name);
+    let context = context_struct(caveat);
+
+    writeln!(out, "pub struct {};\n", name).unwrap();
+    writeln!(out, "impl spicedb_rust::Caveat for {} {{", name).unwrap();
+    writeln!(out, "    type ContextStruct = {};\n", context).unwrap();
+    writeln!(out, "    fn name() -> &'static str {{").unwrap();
+    writeln!(out, "        \"{}\"\n    }}\n}}\n", caveat.name).unwrap();
+
+    writeln!(
+        out,
+        "/// Context of the `{}` caveat, fields left as `None` are omitted so they can be provided",
+        caveat.name
+    )
+    .unwrap();
+    writeln!(out, "/// when checking instead.").unwrap();
+    writeln!(out, "#[derive(Clone, Debug, Default, PartialEq)]").unwrap();
+    writeln!(out, "pub struct {} {{", context).unwrap();
+    for parameter in &caveat.parameters {
+        writeln!(
+            out,
+            "    pub {}: Option<{}>,",
+            field_name(&parameter.name),
+            rust_type(&parameter.parameter_type)
+        )
+        .unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+
+    writeln!(
+        out,
+        "impl From<{}> for spicedb_rust::prost_types::Struct {{",
+        context
+    )
+    .unwrap();
+    if caveat.parameters.is_empty() {
+        writeln!(out, "    fn from(_: {}) -> Self {{", context).unwrap();
+        writeln!(out, "        Self::default()\n    }}\n}}\n").unwrap();
+        return;
+    }
+    writeln!(out, "    fn from(context: {}) -> Self {{", context).unwrap();
+    writeln!(
+        out,
+        "        let mut fields = std::collections::BTreeMap::new();"
+    )
+    .unwrap();
+    for parameter in &caveat.parameters {
+        writeln!(
+            out,
+            "        if let Some(value) = context.{} {{",
+            field_name(&parameter.name)
+        )
+        .unwrap();
+        writeln!(out, "            fields.insert(").unwrap();
+        writeln!(out, "                \"{}\".to_owned(),", parameter.name).unwrap();
+        writeln!(
+            out,
+            "                spicedb_rust::IntoContextValue::into_context_value(value),"
+        )
+        .unwrap();
+        writeln!(out, "            );\n        }}").unwrap();
+    }
+    writeln!(out, "        Self {{ fields }}\n    }}\n}}\n").unwrap();
+}
+
+pub(crate) fn render(codegen: &Codegen, schema: &Schema) -> anyhow::Result<String> {
+    check_names(schema)?;
+    let mut out = String::from("// This file is @generated by spicedb-rust, do not edit.\n\n");
+    for definition in &schema.definitions {
+        render_definition(codegen, &mut out, definition);
+    }
+    for caveat in &schema.caveats {
+        render_caveat(&mut out, caveat);
+    }
+    let trimmed = out.trim_end().len();
+    out.truncate(trimmed);
+    out.push('\n');
+    Ok(out)
+}
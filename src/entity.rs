@@ -55,6 +55,79 @@ pub trait Caveat {
     fn name() -> &'static str;
 }
 
+/// Converts a rust value into the `prost_types::Value` of a caveat context field, following the
+/// JSON-like encoding SpiceDB expects for the CEL parameter types.
+pub trait IntoContextValue {
+    fn into_context_value(self) -> prost_types::Value;
+}
+
+fn context_value(kind: prost_types::value::Kind) -> prost_types::Value {
+    prost_types::Value { kind: Some(kind) }
+}
+
+impl IntoContextValue for prost_types::Value {
+    fn into_context_value(self) -> prost_types::Value {
+        self
+    }
+}
+
+impl IntoContextValue for bool {
+    fn into_context_value(self) -> prost_types::Value {
+        context_value(prost_types::value::Kind::BoolValue(self))
+    }
+}
+
+impl IntoContextValue for f64 {
+    fn into_context_value(self) -> prost_types::Value {
+        context_value(prost_types::value::Kind::NumberValue(self))
+    }
+}
+
+impl IntoContextValue for i64 {
+    fn into_context_value(self) -> prost_types::Value {
+        context_value(prost_types::value::Kind::NumberValue(self as f64))
+    }
+}
+
+impl IntoContextValue for u64 {
+    fn into_context_value(self) -> prost_types::Value {
+        context_value(prost_types::value::Kind::NumberValue(self as f64))
+    }
+}
+
+impl IntoContextValue for String {
+    fn into_context_value(self) -> prost_types::Value {
+        context_value(prost_types::value::Kind::StringValue(self))
+    }
+}
+
+impl<T> IntoContextValue for Vec<T>
+where
+    T: IntoContextValue,
+{
+    fn into_context_value(self) -> prost_types::Value {
+        context_value(prost_types::value::Kind::ListValue(
+            prost_types::ListValue {
+                values: self.into_iter().map(T::into_context_value).collect(),
+            },
+        ))
+    }
+}
+
+impl<T> IntoContextValue for std::collections::HashMap<String, T>
+where
+    T: IntoContextValue,
+{
+    fn into_context_value(self) -> prost_types::Value {
+        context_value(prost_types::value::Kind::StructValue(prost_types::Struct {
+            fields: self
+                .into_iter()
+                .map(|(k, v)| (k, v.into_context_value()))
+                .collect(),
+        }))
+    }
+}
+
 /// Implement the Actor trait for any struct that will represent someone/something taking action in
 /// your system. it could for example be an enum wrapping User/Organization/Service if those are
 /// entities that can take action.
@@ -89,6 +162,14 @@ impl Relation for NoRelations {
     }
 }
 
+impl strum::IntoEnumIterator for NoRelations {
+    type Iterator = std::iter::Empty<NoRelations>;
+
+    fn iter() -> Self::Iterator {
+        std::iter::empty()
+    }
+}
+
 impl FromStr for NoRelations {
     type Err = ();
 
@@ -97,6 +178,24 @@ impl FromStr for NoRelations {
     }
 }
 
+/// Use this type for `Permissions` when implementing `Resource` for something that only has
+/// relations, so it can still be the resource of a relationship.
+pub struct NoPermissions;
+
+impl Permission for NoPermissions {
+    fn name(self) -> &'static str {
+        unreachable!()
+    }
+}
+
+impl strum::IntoEnumIterator for NoPermissions {
+    type Iterator = std::iter::Empty<NoPermissions>;
+
+    fn iter() -> Self::Iterator {
+        std::iter::empty()
+    }
+}
+
 /// Use this type to build your wildcard entity types.
 /// Often there already are wildcard shortcuts like `add_wildcard_relationship` in the
 /// `write_relationships_request` builder that can be used instead of this.
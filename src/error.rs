@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use prost::Message;
+
+use crate::schema::SchemaChange;
+use crate::spicedb::{self, ErrorReason};
+
+const ERROR_INFO_TYPE_URL: &str = "type.googleapis.com/google.rpc.ErrorInfo";
+
+/// `google.rpc.ErrorInfo`, attached by SpiceDB to the details of a failed request
+#[derive(Clone, PartialEq, Message)]
+struct ErrorInfo {
+    #[prost(string, tag = "1")]
+    reason: String,
+    #[prost(string, tag = "2")]
+    domain: String,
+    #[prost(map = "string, string", tag = "3")]
+    metadata: HashMap<String, String>,
+}
+
+/// Error of every request made by this crate.
+///
+/// Failures SpiceDB reports with a well known `ErrorReason` are decoded into their own variants,
+/// all other failed requests end up in `Error::Status`. Variants of failed requests hold the
+/// original `tonic::Status`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A precondition of a write or delete didn't hold
+    PreconditionFailed {
+        filter: Box<spicedb::RelationshipFilter>,
+        status: Box<tonic::Status>,
+    },
+    UnknownDefinition {
+        definition: String,
+        status: Box<tonic::Status>,
+    },
+    UnknownRelationOrPermission {
+        definition: String,
+        name: String,
+        status: Box<tonic::Status>,
+    },
+    UnknownCaveat {
+        caveat: String,
+        status: Box<tonic::Status>,
+    },
+    /// The subject type of a relationship isn't allowed on its relation
+    InvalidSubjectType {
+        definition: String,
+        relation: String,
+        subject_type: String,
+        status: Box<tonic::Status>,
+    },
+    /// A write has more updates than the server allows
+    TooManyUpdates {
+        limit: u64,
+        status: Box<tonic::Status>,
+    },
+    TooManyPreconditions {
+        limit: u64,
+        status: Box<tonic::Status>,
+    },
+    TooManyChecks {
+        limit: u64,
+        status: Box<tonic::Status>,
+    },
+    /// The cursor is invalid or belongs to a different request
+    InvalidCursor {
+        status: Box<tonic::Status>,
+    },
+    ServiceReadOnly {
+        status: Box<tonic::Status>,
+    },
+    /// Any other failed request, `reason` and `metadata` are set if SpiceDB attached an
+    /// `ErrorInfo`
+    Status {
+        reason: Option<ErrorReason>,
+        metadata: HashMap<String, String>,
+        status: Box<tonic::Status>,
+    },
+    /// SpiceDB couldn't be reached
+    Transport(Box<dyn std::error::Error + Send + Sync>),
+    /// An id returned by SpiceDB couldn't be parsed into the `Entity::Id` type
+    IdParse {
+        id: String,
+        expected_type: &'static str,
+    },
+    /// The request was rejected before it was sent, e.g. because a required field is missing
+    InvalidRequest(String),
+    /// A response didn't contain a field SpiceDB always sets
+    InvalidResponse(String),
+    /// Loading or saving a watch checkpoint failed
+    Checkpoint(anyhow::Error),
+    /// Reading or recording the `ZedToken` of a session failed
+    ZedTokenStore(anyhow::Error),
+    /// `apply_schema_change` refused a destructive change under its policy
+    SchemaChangeRefused(Box<SchemaChange>),
+    /// The deployed schema changed since the `SchemaChangePlan` was made
+    StaleSchemaChangePlan,
+    /// Another `Migrator` took over the migration lock, because refreshing it failed for longer
+    /// than the lock timeout
+    MigrationLockLost,
+}
+
+impl Error {
+    pub(crate) fn invalid_response(message: impl Into<String>) -> Self {
+        Error::InvalidResponse(message.into())
+    }
+
+    pub(crate) fn invalid_request(message: impl Into<String>) -> Self {
+        Error::InvalidRequest(message.into())
+    }
+
+    pub(crate) fn id_parse<T>(id: impl Into<String>) -> Self {
+        Error::IdParse {
+            id: id.into(),
+            expected_type: std::any::type_name::<T>(),
+        }
+    }
+
+    /// The failed request's status, `None` for errors that didn't come from SpiceDB
+    pub fn status(&self) -> Option<&tonic::Status> {
+        match self {
+            Error::PreconditionFailed { status, .. }
+            | Error::UnknownDefinition { status, .. }
+            | Error::UnknownRelationOrPermission { status, .. }
+            | Error::UnknownCaveat { status, .. }
+            | Error::InvalidSubjectType { status, .. }
+            | Error::TooManyUpdates { status, .. }
+            | Error::TooManyPreconditions { status, .. }
+            | Error::TooManyChecks { status, .. }
+            | Error::InvalidCursor { status }
+            | Error::ServiceReadOnly { status }
+            | Error::Status { status, .. } => Some(status),
+            _ => None,
+        }
+    }
+
+    /// gRPC code of the failed request, or the closest code for errors raised by this crate
+    pub fn code(&self) -> tonic::Code {
+        if let Some(status) = self.status() {
+            return status.code();
+        }
+        match self {
+            Error::Transport(_) => tonic::Code::Unavailable,
+            Error::InvalidRequest(_) => tonic::Code::InvalidArgument,
+            Error::SchemaChangeRefused(_) => tonic::Code::FailedPrecondition,
+            Error::StaleSchemaChangePlan | Error::MigrationLockLost => tonic::Code::Aborted,
+            _ => tonic::Code::Internal,
+        }
+    }
+
+    /// Copy of the error for each caller of a request that was sent once on behalf of several.
+    /// Errors from SpiceDB are decoded again from their status, other sources keep their message
+    /// only.
+    pub(crate) fn duplicate(&self) -> Self {
+        if let Some(status) = self.status() {
+            return status.clone().into();
+        }
+        match self {
+            Error::Transport(e) => Error::Transport(e.to_string().into()),
+            Error::IdParse { id, expected_type } => Error::IdParse {
+                id: id.clone(),
+                expected_type,
+            },
+            Error::InvalidRequest(message) => Error::InvalidRequest(message.clone()),
+            Error::InvalidResponse(message) => Error::InvalidResponse(message.clone()),
+            Error::Checkpoint(e) => Error::Checkpoint(anyhow::anyhow!("{:#}", e)),
+            Error::ZedTokenStore(e) => Error::ZedTokenStore(anyhow::anyhow!("{:#}", e)),
+            Error::SchemaChangeRefused(change) => Error::SchemaChangeRefused(change.clone()),
+            Error::StaleSchemaChangePlan => Error::StaleSchemaChangePlan,
+            Error::MigrationLockLost => Error::MigrationLockLost,
+            // Variants holding a status were handled above
+            _ => Error::invalid_response(self.to_string()),
+        }
+    }
+
+    /// Converts a `google.rpc.Status` embedded in a response, e.g. the per item errors of
+    /// `CheckBulkPermissions`.
+    pub(crate) fn from_rpc_status(status: crate::generated::google::rpc::Status) -> Self {
+        tonic::Status::with_details(
+            tonic::Code::from_i32(status.code),
+            status.message.clone(),
+            status.encode_to_vec().into(),
+        )
+        .into()
+    }
+}
+
+fn error_info(status: &tonic::Status) -> Option<ErrorInfo> {
+    let details = crate::generated::google::rpc::Status::decode(status.details()).ok()?;
+    details
+        .details
+        .iter()
+        .filter(|any| any.type_url == ERROR_INFO_TYPE_URL)
+        .find_map(|any| ErrorInfo::decode(any.value.as_slice()).ok())
+}
+
+fn is_transport_error(status: &tonic::Status) -> bool {
+    let mut source = std::error::Error::source(status);
+    while let Some(error) = source {
+        if error.is::<tonic::transport::Error>() {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
+
+impl From<tonic::Status> for Error {
+    fn from(status: tonic::Status) -> Self {
+        if status.code() == tonic::Code::Unavailable && is_transport_error(&status) {
+            return Error::Transport(Box::new(status));
+        }
+        let Some(info) = error_info(&status) else {
+            return Error::Status {
+                reason: None,
+                metadata: HashMap::new(),
+                status: Box::new(status),
+            };
+        };
+        let mut metadata = info.metadata;
+        let mut take = |key: &str| metadata.remove(key).unwrap_or_default();
+        let status = Box::new(status);
+        let reason = ErrorReason::from_str_name(&info.reason);
+        match reason {
+            Some(ErrorReason::WriteOrDeletePreconditionFailure) => {
+                let subject_type = take("precondition_subject_type");
+                let optional_subject_filter =
+                    (!subject_type.is_empty()).then(|| spicedb::SubjectFilter {
+                        subject_type,
+                        optional_subject_id: take("precondition_subject_id"),
+                        optional_relation: Some(take("precondition_subject_relation"))
+                            .filter(|r| !r.is_empty())
+                            .map(|relation| spicedb::subject_filter::RelationFilter { relation }),
+                    });
+                let filter = spicedb::RelationshipFilter {
+                    resource_type: take("precondition_resource_type"),
+                    optional_resource_id: take("precondition_resource_id"),
+                    optional_resource_id_prefix: take("precondition_resource_id_prefix"),
+                    optional_relation: take("precondition_relation"),
+                    optional_subject_filter,
+                };
+                Error::PreconditionFailed {
+                    filter: Box::new(filter),
+                    status,
+                }
+            }
+            Some(ErrorReason::UnknownDefinition) => Error::UnknownDefinition {
+                definition: take("definition_name"),
+                status,
+            },
+            Some(ErrorReason::UnknownRelationOrPermission) => Error::UnknownRelationOrPermission {
+                definition: take("definition_name"),
+                name: take("relation_or_permission_name"),
+                status,
+            },
+            Some(ErrorReason::UnknownCaveat) => Error::UnknownCaveat {
+                caveat: take("caveat_name"),
+                status,
+            },
+            Some(ErrorReason::InvalidSubjectType) => Error::InvalidSubjectType {
+                definition: take("definition_name"),
+                relation: take("relation_name"),
+                subject_type: take("subject_type"),
+                status,
+            },
+            Some(ErrorReason::TooManyUpdatesInRequest) => Error::TooManyUpdates {
+                limit: take("maximum_updates_allowed").parse().unwrap_or_default(),
+                status,
+            },
+            Some(ErrorReason::TooManyPreconditionsInRequest) => Error::TooManyPreconditions {
+                limit: take("maximum_preconditions_allowed")
+                    .parse()
+                    .unwrap_or_default(),
+                status,
+            },
+            Some(ErrorReason::TooManyChecksInRequest) => Error::TooManyChecks {
+                limit: take("maximum_checks_allowed").parse().unwrap_or_default(),
+                status,
+            },
+            Some(ErrorReason::InvalidCursor) => Error::InvalidCursor { status },
+            Some(ErrorReason::ServiceReadOnly) => Error::ServiceReadOnly { status },
+            reason => Error::Status {
+                reason,
+                metadata,
+                status,
+            },
+        }
+    }
+}
+
+impl From<tonic::transport::Error> for Error {
+    fn from(error: tonic::transport::Error) -> Self {
+        Error::Transport(Box::new(error))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Transport(e) => write!(f, "could not reach SpiceDB: {}", e),
+            Error::IdParse { id, expected_type } => {
+                write!(f, "could not parse id `{}` as {}", id, expected_type)
+            }
+            Error::InvalidRequest(message) => write!(f, "invalid request: {}", message),
+            Error::InvalidResponse(message) => write!(f, "invalid response: {}", message),
+            Error::Checkpoint(e) => write!(f, "watch checkpoint failed: {}", e),
+            Error::ZedTokenStore(e) => write!(f, "session ZedToken store failed: {}", e),
+            Error::SchemaChangeRefused(change) => {
+                write!(f, "refusing destructive schema change `{}`", change)
+            }
+            Error::StaleSchemaChangePlan => {
+                write!(f, "the deployed schema changed since the plan was made")
+            }
+            Error::MigrationLockLost => {
+                write!(f, "the migration lock was taken over by another runner")
+            }
+            _ => {
+                let status = self.status().expect("variant of a failed request");
+                write!(f, "{:?}: {}", status.code(), status.message())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Transport(e) => Some(e.as_ref()),
+            Error::Checkpoint(e) | Error::ZedTokenStore(e) => Some(e.as_ref()),
+            _ => self.status().map(|s| s as _),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use futures::TryStreamExt;
+use tokio_stream::{Stream, StreamExt};
+
+use crate::experimental::SpiceDBExperimentalClient;
+use crate::grpc::GrpcResult;
+use crate::permission::resolve_consistency;
+use crate::session::Session;
+use crate::spicedb;
+use crate::spicedb::wrappers::{BulkExportRelationshipsResponse, Consistency, Relationship};
+use crate::Error;
+
+#[derive(Clone, Debug)]
+pub struct BulkExportRelationshipsRequest {
+    client: SpiceDBExperimentalClient,
+    request: spicedb::BulkExportRelationshipsRequest,
+    session: Option<Session>,
+    default_consistency: Option<Consistency>,
+}
+
+impl BulkExportRelationshipsRequest {
+    pub fn new(client: SpiceDBExperimentalClient) -> Self {
+        let request = spicedb::BulkExportRelationshipsRequest {
+            ..Default::default()
+        };
+        BulkExportRelationshipsRequest {
+            client,
+            request,
+            session: None,
+            default_consistency: None,
+        }
+    }
+
+    pub(crate) fn session(&mut self, session: Option<Session>) -> &mut Self {
+        self.session = session;
+        self
+    }
+
+    pub(crate) fn default_consistency(&mut self, consistency: Option<Consistency>) -> &mut Self {
+        self.default_consistency = consistency;
+        self
+    }
+
+    pub fn with_consistency(&mut self, consistency: Consistency) -> &mut Self {
+        self.request.consistency = Some(consistency.into());
+        self
+    }
+
+    /// Maximum number of relationships per page, the server picks a size if unset.
+    pub fn with_limit(&mut self, limit: u32) -> &mut Self {
+        self.request.optional_limit = limit;
+        self
+    }
+
+    /// Resume an export after the page the cursor was taken from.
+    pub fn with_cursor(&mut self, cursor: spicedb::Cursor) -> &mut Self {
+        self.request.optional_cursor = Some(cursor);
+        self
+    }
+
+    pub fn with_relationship_filter(&mut self, filter: spicedb::RelationshipFilter) -> &mut Self {
+        self.request.optional_relationship_filter = Some(filter);
+        self
+    }
+
+    /// Streams the export page by page, to resume an interrupted export pass the
+    /// `after_result_cursor` of the last fully processed page to `with_cursor`.
+    pub async fn send_stream(
+        mut self,
+    ) -> GrpcResult<impl Stream<Item = GrpcResult<BulkExportRelationshipsResponse>>> {
+        resolve_consistency(
+            &mut self.request.consistency,
+            self.session.as_ref(),
+            self.default_consistency.take(),
+        )
+        .await?;
+        let resp = self
+            .client
+            .bulk_export_relationships(self.request)
+            .await?
+            .into_inner();
+        Ok(resp.map(|r| r.map_err(Error::from).and_then(TryInto::try_into)))
+    }
+
+    /// Streams the exported relationships one by one, without the page cursors.
+    pub async fn send_relationships_stream(
+        self,
+    ) -> GrpcResult<impl Stream<Item = GrpcResult<Relationship>>> {
+        let pages = self.send_stream().await?;
+        Ok(pages
+            .map_ok(|page| tokio_stream::iter(page.relationships.into_iter().map(Ok)))
+            .try_flatten())
+    }
+}
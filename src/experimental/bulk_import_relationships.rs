@@ -0,0 +1,65 @@
+use futures::StreamExt;
+use tokio_stream::Stream;
+
+use crate::experimental::SpiceDBExperimentalClient;
+use crate::grpc::GrpcResult;
+use crate::spicedb;
+use crate::Error;
+
+const DEFAULT_CHUNK_SIZE: usize = 1_000;
+
+/// Streams relationships into SpiceDB, sending them in chunks of `chunk_size` per request
+/// message. Chunks are only pulled from the input as fast as the server accepts them.
+#[derive(Clone, Debug)]
+pub struct BulkImportRelationshipsRequest {
+    client: SpiceDBExperimentalClient,
+    chunk_size: usize,
+}
+
+impl BulkImportRelationshipsRequest {
+    pub fn new(client: SpiceDBExperimentalClient) -> Self {
+        BulkImportRelationshipsRequest {
+            client,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Number of relationships per request message, defaults to 1000.
+    pub fn chunk_size(&mut self, chunk_size: usize) -> &mut Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Imports all relationships and returns the number of relationships loaded.
+    pub async fn send<I>(self, relationships: I) -> GrpcResult<u64>
+    where
+        I: IntoIterator,
+        I::Item: Into<spicedb::Relationship> + Send,
+        I::IntoIter: Send + 'static,
+    {
+        self.send_stream(tokio_stream::iter(relationships)).await
+    }
+
+    /// Imports all relationships yielded by the stream and returns the number of relationships
+    /// loaded.
+    pub async fn send_stream<S>(mut self, relationships: S) -> GrpcResult<u64>
+    where
+        S: Stream + Send + 'static,
+        S::Item: Into<spicedb::Relationship> + Send,
+    {
+        if self.chunk_size == 0 {
+            return Err(Error::invalid_request("chunk_size must be greater than 0"));
+        }
+        let requests = relationships.chunks(self.chunk_size).map(|chunk| {
+            spicedb::BulkImportRelationshipsRequest {
+                relationships: chunk.into_iter().map(Into::into).collect(),
+            }
+        });
+        let resp = self
+            .client
+            .bulk_import_relationships(requests)
+            .await?
+            .into_inner();
+        Ok(resp.num_loaded)
+    }
+}
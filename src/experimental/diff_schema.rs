@@ -0,0 +1,46 @@
+use crate::experimental::SpiceDBExperimentalClient;
+use crate::grpc::GrpcResult;
+use crate::schema::SchemaChangePlan;
+use crate::spicedb;
+use crate::spicedb::wrappers::Consistency;
+
+#[derive(Clone, Debug)]
+pub struct DiffSchemaRequest {
+    client: SpiceDBExperimentalClient,
+    request: spicedb::ExperimentalDiffSchemaRequest,
+}
+
+impl DiffSchemaRequest {
+    pub fn new(client: SpiceDBExperimentalClient, comparison_schema: String) -> Self {
+        let request = spicedb::ExperimentalDiffSchemaRequest {
+            comparison_schema,
+            ..Default::default()
+        };
+        DiffSchemaRequest { client, request }
+    }
+
+    pub fn with_consistency(&mut self, consistency: Consistency) -> &mut Self {
+        self.request.consistency = Some(consistency.into());
+        self
+    }
+
+    /// Diffs the deployed schema against the comparison schema, changes are reported from the
+    /// perspective of the deployed schema, e.g. `DefinitionAdded` is only in the comparison.
+    pub async fn send(mut self) -> GrpcResult<SchemaChangePlan> {
+        let schema = self.request.comparison_schema.clone();
+        let resp = self
+            .client
+            .experimental_diff_schema(self.request)
+            .await?
+            .into_inner();
+        Ok(SchemaChangePlan {
+            schema,
+            changes: resp
+                .diffs
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+            read_at: resp.read_at,
+        })
+    }
+}
@@ -0,0 +1,15 @@
+mod bulk_export_relationships;
+mod bulk_import_relationships;
+mod diff_schema;
+mod reflect_schema;
+
+use crate::grpc::AuthenticatedChannel;
+use crate::spicedb;
+
+pub type SpiceDBExperimentalClient =
+    spicedb::experimental_service_client::ExperimentalServiceClient<AuthenticatedChannel>;
+
+pub use bulk_export_relationships::BulkExportRelationshipsRequest;
+pub use bulk_import_relationships::BulkImportRelationshipsRequest;
+pub use diff_schema::DiffSchemaRequest;
+pub use reflect_schema::ReflectSchemaRequest;
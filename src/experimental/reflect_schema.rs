@@ -0,0 +1,40 @@
+use crate::experimental::SpiceDBExperimentalClient;
+use crate::grpc::GrpcResult;
+use crate::schema::Schema;
+use crate::spicedb;
+use crate::spicedb::wrappers::Consistency;
+
+#[derive(Clone, Debug)]
+pub struct ReflectSchemaRequest {
+    client: SpiceDBExperimentalClient,
+    request: spicedb::ExperimentalReflectSchemaRequest,
+}
+
+impl ReflectSchemaRequest {
+    pub fn new(client: SpiceDBExperimentalClient) -> Self {
+        let request = spicedb::ExperimentalReflectSchemaRequest {
+            ..Default::default()
+        };
+        ReflectSchemaRequest { client, request }
+    }
+
+    pub fn with_consistency(&mut self, consistency: Consistency) -> &mut Self {
+        self.request.consistency = Some(consistency.into());
+        self
+    }
+
+    /// Filters are applied in an OR fashion, every name in a filter is matched as a prefix.
+    pub fn add_filter(&mut self, filter: spicedb::ExpSchemaFilter) -> &mut Self {
+        self.request.optional_filters.push(filter);
+        self
+    }
+
+    pub async fn send(mut self) -> GrpcResult<Schema> {
+        let resp = self
+            .client
+            .experimental_reflect_schema(self.request)
+            .await?
+            .into_inner();
+        Ok(resp.into())
+    }
+}
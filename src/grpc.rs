@@ -5,7 +5,7 @@ use tonic::transport::Channel;
 
 pub type AuthenticatedChannel = InterceptedService<Channel, BearerTokenInterceptor>;
 
-pub type GrpcResult<T> = Result<T, tonic::Status>;
+pub type GrpcResult<T> = Result<T, crate::Error>;
 
 #[derive(Clone, Debug)]
 pub struct BearerTokenInterceptor {
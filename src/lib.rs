@@ -3,19 +3,37 @@ mod generated {
     include!(concat!(env!("OUT_DIR"), "/_includes.rs"));
 }
 
+mod backup;
+mod batch;
+pub mod cache;
 mod client;
+mod client_builder;
+pub mod codegen;
 mod entity;
+mod error;
+mod experimental;
 mod grpc;
+pub mod migrations;
 pub mod spicedb;
 
 mod permission;
-mod schema;
+mod retry;
+pub mod schema;
+pub mod session;
+mod single_flight;
+pub mod watch;
 
+pub use cache::CachedSpiceDBClient;
 pub use client::SpiceDBClient;
+pub use client_builder::SpiceDBClientBuilder;
 
-pub type Error = tonic::Status;
+pub use error::Error;
+pub use retry::{RetryEvent, RetryPolicy};
 
-pub use strum::IntoStaticStr;
+pub use prost_types;
+pub use strum::{EnumIter, IntoEnumIterator, IntoStaticStr};
 
 pub use entity::*;
 pub use spicedb::relationship_update::Operation as RelationshipOperation;
+#[cfg(feature = "derive")]
+pub use spicedb_rust_derive::{Actor, Entity};
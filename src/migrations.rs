@@ -0,0 +1,488 @@
+//! Versioned schema migrations, similar to SQL migration tools.
+//!
+//! Each `Migration` holds the full schema text of its version plus optional `cleanup` and
+//! `backfill` closures, which run before and after the schema is written. The applied version
+//! and a lock serializing concurrent runners are stored as relationships on the reserved
+//! `spicedb_rust_migration` definition, which `Migrator` appends to every schema it writes.
+//!
+//! ```ignore
+//! let migrator = Migrator::new([
+//!     Migration::new(1, include_str!("schema_v1.zed")),
+//!     Migration::new(2, include_str!("schema_v2.zed")).backfill(|client| async move {
+//!         let mut request = client.create_relationships_request();
+//!         // ..
+//!         request.send().await?;
+//!         Ok(())
+//!     }),
+//! ]);
+//! migrator.run(&client).await?;
+//! ```
+//!
+//! A step whose closures fail is retried by the next run, so closures must be idempotent, e.g.
+//! by using `RelationshipOperation::Touch` instead of `Create`.
+//!
+//! The lock is refreshed while the steps run. If refreshing it fails for longer than the lock
+//! timeout another runner takes it over, the run then stops before its next step or write with
+//! `Error::MigrationLockLost`.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio_stream::StreamExt;
+
+use crate::grpc::GrpcResult;
+use crate::spicedb;
+use crate::spicedb::precondition::Operation as PreconditionOperation;
+use crate::spicedb::wrappers::Consistency;
+use crate::Error;
+use crate::{RelationshipOperation, SpiceDBClient};
+
+/// Name of the definition holding the migration state
+pub const MIGRATION_DEFINITION: &str = "spicedb_rust_migration";
+
+const MIGRATION_SCHEMA: &str = "definition spicedb_rust_migration {
+    relation applied: spicedb_rust_migration
+    relation holder: spicedb_rust_migration
+}";
+
+/// `spicedb_rust_migration:version#applied@spicedb_rust_migration:<version>`
+const VERSION_ID: &str = "version";
+/// `spicedb_rust_migration:lock#holder@spicedb_rust_migration:<refreshed at, unix millis>`
+const LOCK_ID: &str = "lock";
+
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+type StepFn = Box<
+    dyn Fn(SpiceDBClient) -> Pin<Box<dyn Future<Output = GrpcResult<()>> + Send>> + Send + Sync,
+>;
+
+pub struct Migration {
+    version: u32,
+    schema: String,
+    cleanup: Option<StepFn>,
+    backfill: Option<StepFn>,
+}
+
+impl Migration {
+    /// `schema` is the complete schema at `version`, without the `spicedb_rust_migration`
+    /// definition.
+    pub fn new(version: u32, schema: impl Into<String>) -> Self {
+        Migration {
+            version,
+            schema: schema.into(),
+            cleanup: None,
+            backfill: None,
+        }
+    }
+
+    /// Runs before the schema is written, e.g. to delete the relationships of a relation the
+    /// schema removes.
+    pub fn cleanup<F, Fut>(mut self, cleanup: F) -> Self
+    where
+        F: Fn(SpiceDBClient) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = GrpcResult<()>> + Send + 'static,
+    {
+        self.cleanup = Some(Box::new(move |client| Box::pin(cleanup(client))));
+        self
+    }
+
+    /// Runs after the schema is written, e.g. to populate a relation the schema adds.
+    pub fn backfill<F, Fut>(mut self, backfill: F) -> Self
+    where
+        F: Fn(SpiceDBClient) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = GrpcResult<()>> + Send + 'static,
+    {
+        self.backfill = Some(Box::new(move |client| Box::pin(backfill(client))));
+        self
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+}
+
+pub struct Migrator {
+    migrations: Vec<Migration>,
+    lock_timeout: Duration,
+}
+
+impl Migrator {
+    pub fn new(migrations: impl IntoIterator<Item = Migration>) -> Self {
+        Migrator {
+            migrations: migrations.into_iter().collect(),
+            lock_timeout: Duration::from_secs(300),
+        }
+    }
+
+    /// A lock not refreshed for longer than `timeout` is considered abandoned by a crashed
+    /// runner and taken over, defaults to 5 minutes. Runners refresh their lock every third of
+    /// `timeout`.
+    pub fn lock_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.lock_timeout = timeout;
+        self
+    }
+
+    /// Applies all migrations newer than the applied version in order, returns the versions
+    /// applied by this call. Concurrent runs, e.g. from several replicas, wait for each other and
+    /// skip the steps applied in the meantime.
+    pub async fn run(&self, client: &SpiceDBClient) -> GrpcResult<Vec<u32>> {
+        if self
+            .migrations
+            .windows(2)
+            .any(|w| w[0].version >= w[1].version)
+        {
+            return Err(Error::invalid_request(
+                "migration versions must be strictly increasing",
+            ));
+        }
+        let bootstrapped = bootstrap(client).await?;
+        let lock = self.acquire_lock(client).await?;
+        let result = self.apply_pending(client, &lock, bootstrapped).await;
+        let released = lock.release(client).await;
+        let applied = result?;
+        released?;
+        Ok(applied)
+    }
+
+    /// Returns the applied version, 0 if no migration ran yet.
+    pub async fn current_version(client: &SpiceDBClient) -> GrpcResult<u32> {
+        let filter = spicedb::relationship_filter_raw(
+            MIGRATION_DEFINITION,
+            Some(VERSION_ID),
+            None::<String>,
+            Some("applied"),
+            None,
+        );
+        let holders = read_subject_ids(client, filter).await?;
+        Ok(holders
+            .iter()
+            .filter_map(|id| id.parse().ok())
+            .max()
+            .unwrap_or(0))
+    }
+
+    async fn apply_pending(
+        &self,
+        client: &SpiceDBClient,
+        lock: &Lock,
+        bootstrapped: bool,
+    ) -> GrpcResult<Vec<u32>> {
+        let mut current = Self::current_version(client).await?;
+        // `bootstrap` writes the schema it read before taking the lock, which may have replaced
+        // the schema of the version another runner applied meanwhile
+        if bootstrapped {
+            if let Some(migration) = self.migrations.iter().find(|m| m.version == current) {
+                write_schema(client, lock, migration).await?;
+            }
+        }
+        let pending = self.migrations.iter().filter(|m| m.version > current);
+        let mut applied = vec![];
+        for migration in pending.collect::<Vec<_>>() {
+            if let Some(cleanup) = &migration.cleanup {
+                check_lock(client, &lock.hold().await?).await?;
+                cleanup(client.clone()).await?;
+            }
+            write_schema(client, lock, migration).await?;
+            if let Some(backfill) = &migration.backfill {
+                check_lock(client, &lock.hold().await?).await?;
+                backfill(client.clone()).await?;
+            }
+            set_version(client, lock, current, migration.version).await?;
+            current = migration.version;
+            applied.push(migration.version);
+        }
+        Ok(applied)
+    }
+
+    async fn acquire_lock(&self, client: &SpiceDBClient) -> GrpcResult<Lock> {
+        loop {
+            let holder = unix_millis().to_string();
+            let mut request = client.create_relationships_request();
+            request
+                .add_precondition_raw(spicedb::precondition_raw(
+                    PreconditionOperation::MustNotMatch,
+                    MIGRATION_DEFINITION,
+                    Some(LOCK_ID),
+                    None::<String>,
+                    Some("holder"),
+                    None,
+                ))
+                .add_relationship_raw(lock_update(RelationshipOperation::Create, &holder));
+            match request.send().await {
+                Ok(_) => return Ok(Lock::new(client, holder, self.lock_timeout / 3)),
+                Err(e) if e.code() == tonic::Code::FailedPrecondition => {}
+                Err(e) => return Err(e),
+            }
+
+            let filter = spicedb::relationship_filter_raw(
+                MIGRATION_DEFINITION,
+                Some(LOCK_ID),
+                None::<String>,
+                Some("holder"),
+                None,
+            );
+            for stale in read_subject_ids(client, filter).await? {
+                let refreshed_at = stale.parse::<u128>().unwrap_or(0);
+                if unix_millis().saturating_sub(refreshed_at) > self.lock_timeout.as_millis() {
+                    release_lock(client, &stale).await?;
+                }
+            }
+            tokio::time::sleep(LOCK_POLL_INTERVAL).await;
+        }
+    }
+}
+
+/// The lock of a running `Migrator`, refreshed in the background until it's released.
+struct Lock {
+    /// Subject id of the lock relationship, `None` once another runner took the lock over.
+    holder: Arc<tokio::sync::Mutex<Option<String>>>,
+    refresh: tokio::task::JoinHandle<()>,
+}
+
+impl Lock {
+    fn new(client: &SpiceDBClient, holder: String, interval: Duration) -> Self {
+        let holder = Arc::new(tokio::sync::Mutex::new(Some(holder)));
+        let refresh = tokio::spawn(refresh_lock(client.clone(), holder.clone(), interval));
+        Lock { holder, refresh }
+    }
+
+    /// Fails if the lock was taken over, the holder doesn't change while the guard is held.
+    async fn hold(&self) -> GrpcResult<tokio::sync::MappedMutexGuard<'_, String>> {
+        tokio::sync::MutexGuard::try_map(self.holder.lock().await, Option::as_mut)
+            .map_err(|_| Error::MigrationLockLost)
+    }
+
+    async fn release(self, client: &SpiceDBClient) -> GrpcResult<()> {
+        self.refresh.abort();
+        let holder = self.holder.lock().await.take();
+        match holder {
+            Some(holder) => release_lock(client, &holder).await,
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Lock {
+    /// A cancelled run stops refreshing, so its lock times out instead of being held forever
+    fn drop(&mut self) {
+        self.refresh.abort();
+    }
+}
+
+/// Moves the lock to a holder with the current time every `interval`, as long as it's still
+/// held by this runner.
+async fn refresh_lock(
+    client: SpiceDBClient,
+    holder: Arc<tokio::sync::Mutex<Option<String>>>,
+    interval: Duration,
+) {
+    loop {
+        tokio::time::sleep(interval).await;
+        let mut holder = holder.lock().await;
+        let Some(current) = holder.clone() else {
+            return;
+        };
+        let next = unix_millis().to_string();
+        if next == current {
+            continue;
+        }
+        let mut request = client.create_relationships_request();
+        request
+            .add_precondition_raw(held_by(&current))
+            .add_relationship_raw(lock_update(RelationshipOperation::Delete, &current))
+            .add_relationship_raw(lock_update(RelationshipOperation::Create, &next));
+        match request.send().await {
+            Ok(_) => *holder = Some(next),
+            Err(e) if e.code() == tonic::Code::FailedPrecondition => {
+                *holder = None;
+                return;
+            }
+            // Retried with the next refresh, the lock is only taken over after the timeout
+            Err(_) => {}
+        }
+    }
+}
+
+/// Fails with `Error::MigrationLockLost` unless the lock relationship is still held by
+/// `holder`, e.g. because another runner took it over after a failed refresh.
+async fn check_lock(client: &SpiceDBClient, holder: &str) -> GrpcResult<()> {
+    let filter = spicedb::relationship_filter_raw(
+        MIGRATION_DEFINITION,
+        Some(LOCK_ID),
+        None::<String>,
+        Some("holder"),
+        None,
+    );
+    if read_subject_ids(client, filter)
+        .await?
+        .iter()
+        .any(|h| h == holder)
+    {
+        Ok(())
+    } else {
+        Err(Error::MigrationLockLost)
+    }
+}
+
+/// Writes the schema of `migration`. Schema writes can't have preconditions, the lock is checked
+/// right before instead.
+async fn write_schema(
+    client: &SpiceDBClient,
+    lock: &Lock,
+    migration: &Migration,
+) -> GrpcResult<()> {
+    let holder = lock.hold().await?;
+    check_lock(client, &holder).await?;
+    client
+        .write_schema(format!(
+            "{}\n\n{}",
+            migration.schema.trim(),
+            MIGRATION_SCHEMA
+        ))
+        .await?;
+    Ok(())
+}
+
+/// Adds the migration definition to the deployed schema if it's missing, keeping the rest.
+/// Returns whether it was added.
+async fn bootstrap(client: &SpiceDBClient) -> GrpcResult<bool> {
+    let schema = match client.read_schema().await {
+        Ok(resp) => resp.schema_text,
+        Err(e) if e.code() == tonic::Code::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    // Looked up through reflection rather than `parse_schema`, which may not know all the syntax
+    // the deployed schema uses
+    if !schema.trim().is_empty()
+        && client
+            .reflect_schema()
+            .await?
+            .definition(MIGRATION_DEFINITION)
+            .is_some()
+    {
+        return Ok(false);
+    }
+    client
+        .write_schema(format!("{}\n\n{}", schema.trim(), MIGRATION_SCHEMA))
+        .await?;
+    Ok(true)
+}
+
+async fn release_lock(client: &SpiceDBClient, holder: &str) -> GrpcResult<()> {
+    let mut request = client.create_relationships_request();
+    request.add_relationship_raw(lock_update(RelationshipOperation::Delete, holder));
+    request.send().await?;
+    Ok(())
+}
+
+fn held_by(holder: &str) -> spicedb::Precondition {
+    spicedb::precondition_raw(
+        PreconditionOperation::MustMatch,
+        MIGRATION_DEFINITION,
+        Some(LOCK_ID),
+        None::<String>,
+        Some("holder"),
+        Some(spicedb::subject_filter_raw(
+            MIGRATION_DEFINITION,
+            Some(holder),
+            None::<String>,
+        )),
+    )
+}
+
+/// Moves the version from `from` to `to`, failing if another runner changed it meanwhile or took
+/// over the lock.
+async fn set_version(client: &SpiceDBClient, lock: &Lock, from: u32, to: u32) -> GrpcResult<()> {
+    let holder = lock.hold().await?;
+    let mut request = client.create_relationships_request();
+    request.add_precondition_raw(held_by(&holder));
+    if from > 0 {
+        request
+            .add_precondition_raw(spicedb::precondition_raw(
+                PreconditionOperation::MustMatch,
+                MIGRATION_DEFINITION,
+                Some(VERSION_ID),
+                None::<String>,
+                Some("applied"),
+                Some(spicedb::subject_filter_raw(
+                    MIGRATION_DEFINITION,
+                    Some(from.to_string()),
+                    None::<String>,
+                )),
+            ))
+            .add_relationship_raw(state_update(
+                RelationshipOperation::Delete,
+                VERSION_ID,
+                "applied",
+                &from.to_string(),
+            ));
+    }
+    request.add_relationship_raw(state_update(
+        RelationshipOperation::Touch,
+        VERSION_ID,
+        "applied",
+        &to.to_string(),
+    ));
+    match request.send().await {
+        Ok(_) => Ok(()),
+        Err(Error::PreconditionFailed { filter, .. }) if filter.optional_resource_id == LOCK_ID => {
+            Err(Error::MigrationLockLost)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn lock_update(operation: RelationshipOperation, holder: &str) -> spicedb::RelationshipUpdate {
+    state_update(operation, LOCK_ID, "holder", holder)
+}
+
+fn state_update(
+    operation: RelationshipOperation,
+    id: &str,
+    relation: &str,
+    subject_id: &str,
+) -> spicedb::RelationshipUpdate {
+    spicedb::RelationshipUpdate {
+        operation: operation as i32,
+        relationship: Some(spicedb::Relationship {
+            resource: Some(spicedb::ObjectReference {
+                object_type: MIGRATION_DEFINITION.to_owned(),
+                object_id: id.to_owned(),
+            }),
+            relation: relation.to_owned(),
+            subject: Some(spicedb::subject_reference_raw(
+                subject_id,
+                MIGRATION_DEFINITION,
+                None::<String>,
+            )),
+            optional_caveat: None,
+        }),
+    }
+}
+
+async fn read_subject_ids(
+    client: &SpiceDBClient,
+    filter: spicedb::RelationshipFilter,
+) -> GrpcResult<Vec<String>> {
+    let mut request = client.read_relationships_request();
+    request
+        .with_relationship_filter(filter)
+        .with_consistency(Consistency::FullyConsistent);
+    let mut stream = Box::pin(request.send().await?);
+    let mut ids = vec![];
+    while let Some(resp) = stream.next().await {
+        for relationship in resp?.relationships {
+            ids.push(relationship.subject.object.object_id);
+        }
+    }
+    Ok(ids)
+}
+
+fn unix_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
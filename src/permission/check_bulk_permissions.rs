@@ -0,0 +1,125 @@
+use crate::grpc::GrpcResult;
+use crate::permission::{resolve_consistency, SpiceDBPermissionClient};
+use crate::session::Session;
+use crate::spicedb::object_reference;
+use crate::spicedb::wrappers::{CheckBulkPermissionsResponse, Consistency};
+use crate::{spicedb, Actor, Permission, Resource};
+use crate::{Error, RetryPolicy};
+
+#[derive(Clone, Debug)]
+pub struct CheckBulkPermissionsRequest {
+    client: SpiceDBPermissionClient,
+    request: spicedb::CheckBulkPermissionsRequest,
+    retry: RetryPolicy,
+    session: Option<Session>,
+    default_consistency: Option<Consistency>,
+}
+
+impl CheckBulkPermissionsRequest {
+    pub fn new(client: SpiceDBPermissionClient) -> Self {
+        let request = spicedb::CheckBulkPermissionsRequest {
+            ..Default::default()
+        };
+        CheckBulkPermissionsRequest {
+            client,
+            request,
+            retry: RetryPolicy::never(),
+            session: None,
+            default_consistency: None,
+        }
+    }
+
+    /// Overrides the retry policy of the client for this request.
+    pub fn retry_policy(&mut self, policy: RetryPolicy) -> &mut Self {
+        self.retry = policy;
+        self
+    }
+
+    pub(crate) fn session(&mut self, session: Option<Session>) -> &mut Self {
+        self.session = session;
+        self
+    }
+
+    pub(crate) fn default_consistency(&mut self, consistency: Option<Consistency>) -> &mut Self {
+        self.default_consistency = consistency;
+        self
+    }
+
+    pub fn consistency(&mut self, consistency: Consistency) -> &mut Self {
+        self.request.consistency = Some(consistency.into());
+        self
+    }
+
+    pub(crate) fn consistency_raw(
+        &mut self,
+        consistency: Option<spicedb::Consistency>,
+    ) -> &mut Self {
+        self.request.consistency = consistency;
+        self
+    }
+
+    /// Adds a check for `permission` on resource `R`, items can target different `Resource`
+    /// types within the same request.
+    pub fn add_item<R>(
+        &mut self,
+        actor: &impl Actor,
+        resource_id: impl Into<R::Id>,
+        permission: R::Permissions,
+    ) -> &mut Self
+    where
+        R: Resource,
+    {
+        self.add_item_raw(spicedb::CheckBulkPermissionsRequestItem {
+            resource: Some(object_reference::<R>(resource_id.into())),
+            permission: permission.name().to_owned(),
+            subject: Some(actor.to_subject()),
+            context: None,
+        })
+    }
+
+    pub fn add_item_with_context<R>(
+        &mut self,
+        actor: &impl Actor,
+        resource_id: impl Into<R::Id>,
+        permission: R::Permissions,
+        context: impl Into<prost_types::Struct>,
+    ) -> &mut Self
+    where
+        R: Resource,
+    {
+        self.add_item_raw(spicedb::CheckBulkPermissionsRequestItem {
+            resource: Some(object_reference::<R>(resource_id.into())),
+            permission: permission.name().to_owned(),
+            subject: Some(actor.to_subject()),
+            context: Some(context.into()),
+        })
+    }
+
+    pub fn add_item_raw(&mut self, item: spicedb::CheckBulkPermissionsRequestItem) -> &mut Self {
+        self.request.items.push(item);
+        self
+    }
+
+    /// Results are returned in the same order the items were added.
+    pub async fn send(mut self) -> GrpcResult<CheckBulkPermissionsResponse> {
+        if self.request.items.is_empty() {
+            return Err(Error::invalid_request("at least one item is required"));
+        }
+        resolve_consistency(
+            &mut self.request.consistency,
+            self.session.as_ref(),
+            self.default_consistency.take(),
+        )
+        .await?;
+        let resp = self
+            .retry
+            .run("CheckBulkPermissions", || {
+                let mut client = self.client.clone();
+                let request = self.request.clone();
+                async move { client.check_bulk_permissions(request).await }
+            })
+            .await?
+            .into_inner();
+        Ok(resp.into())
+    }
+}
@@ -1,12 +1,23 @@
+use std::sync::Arc;
+
+use prost::Message;
+
 use crate::grpc::GrpcResult;
-use crate::permission::SpiceDBPermissionClient;
+use crate::permission::{resolve_consistency, SpiceDBPermissionClient};
+use crate::session::Session;
+use crate::single_flight::SingleFlights;
 use crate::spicedb::wrappers::Consistency;
 use crate::{spicedb, Actor, Permission, Resource};
+use crate::{Error, RetryPolicy};
 
 #[derive(Debug)]
 pub struct CheckPermissionRequest<R> {
     client: SpiceDBPermissionClient,
     request: spicedb::CheckPermissionRequest,
+    retry: RetryPolicy,
+    session: Option<Session>,
+    default_consistency: Option<Consistency>,
+    single_flight: Option<Arc<SingleFlights>>,
     _phantom: std::marker::PhantomData<R>,
 }
 
@@ -21,15 +32,45 @@ where
         CheckPermissionRequest {
             client,
             request,
+            retry: RetryPolicy::never(),
+            session: None,
+            default_consistency: None,
+            single_flight: None,
             _phantom: std::marker::PhantomData,
         }
     }
 
+    /// Overrides the retry policy of the client for this request.
+    pub fn retry_policy(&mut self, policy: RetryPolicy) -> &mut Self {
+        self.retry = policy;
+        self
+    }
+
+    pub(crate) fn session(&mut self, session: Option<Session>) -> &mut Self {
+        self.session = session;
+        self
+    }
+
+    pub(crate) fn default_consistency(&mut self, consistency: Option<Consistency>) -> &mut Self {
+        self.default_consistency = consistency;
+        self
+    }
+
+    pub(crate) fn single_flight(&mut self, flights: Option<Arc<SingleFlights>>) -> &mut Self {
+        self.single_flight = flights;
+        self
+    }
+
     pub fn permission(&mut self, permission: R::Permissions) -> &mut Self {
         permission.name().clone_into(&mut self.request.permission);
         self
     }
 
+    pub fn permission_raw(&mut self, permission: impl Into<String>) -> &mut Self {
+        self.request.permission = permission.into();
+        self
+    }
+
     pub fn enable_tracing(&mut self) -> &mut Self {
         self.request.with_tracing = true;
         self
@@ -61,19 +102,41 @@ where
 
     pub async fn send(mut self) -> GrpcResult<spicedb::CheckPermissionResponse> {
         if self.request.resource.is_none() {
-            return Err(tonic::Status::invalid_argument("resource is required"));
+            return Err(Error::invalid_request("resource is required"));
         }
         if self.request.permission.is_empty() {
-            return Err(tonic::Status::invalid_argument("permission is required"));
+            return Err(Error::invalid_request("permission is required"));
         }
         if self.request.subject.is_none() {
-            return Err(tonic::Status::invalid_argument("subject is required"));
+            return Err(Error::invalid_request("subject is required"));
         }
-        let resp = self
-            .client
-            .check_permission(self.request)
-            .await?
-            .into_inner();
-        Ok(resp)
+        resolve_consistency(
+            &mut self.request.consistency,
+            self.session.as_ref(),
+            self.default_consistency.take(),
+        )
+        .await?;
+        let Some(flights) = self.single_flight else {
+            return check_permission(self.client, self.request, self.retry).await;
+        };
+        let key = self.request.encode_to_vec();
+        let call = check_permission(self.client, self.request, self.retry);
+        flights.check_permission.run(key, call).await
     }
 }
+
+async fn check_permission(
+    client: SpiceDBPermissionClient,
+    request: spicedb::CheckPermissionRequest,
+    retry: RetryPolicy,
+) -> GrpcResult<spicedb::CheckPermissionResponse> {
+    let resp = retry
+        .run("CheckPermission", || {
+            let mut client = client.clone();
+            let request = request.clone();
+            async move { client.check_permission(request).await }
+        })
+        .await?
+        .into_inner();
+    Ok(resp)
+}
@@ -1,8 +1,11 @@
 use self::spicedb::delete_relationships_response::DeletionProgress;
 use self::spicedb::precondition::Operation;
 use crate::entity::{Relation, Resource};
+use crate::grpc::GrpcResult;
 use crate::permission::SpiceDBPermissionClient;
+use crate::session::Session;
 use crate::spicedb;
+use crate::{Error, RetryPolicy};
 
 #[derive(Debug)]
 pub struct DeleteRelationshipsRequest<R>
@@ -11,6 +14,8 @@ where
 {
     client: SpiceDBPermissionClient,
     request: spicedb::DeleteRelationshipsRequest,
+    retry: RetryPolicy,
+    session: Option<Session>,
     _phantom: std::marker::PhantomData<R>,
 }
 
@@ -30,10 +35,18 @@ where
                 optional_limit: 0,
                 optional_allow_partial_deletions: false,
             },
+            retry: RetryPolicy::never(),
+            session: None,
             _phantom: std::marker::PhantomData,
         }
     }
 
+    /// Overrides the retry policy of the client for this request.
+    pub fn retry_policy(&mut self, policy: RetryPolicy) -> &mut Self {
+        self.retry = policy;
+        self
+    }
+
     pub fn add_precondition<R2>(
         &mut self,
         operation: Operation,
@@ -103,25 +116,32 @@ where
         self
     }
 
+    pub(crate) fn session(&mut self, session: Option<Session>) -> &mut Self {
+        self.session = session;
+        self
+    }
+
     pub async fn send(
-        mut self,
-    ) -> Result<
-        (
-            spicedb::ZedToken,
-            spicedb::delete_relationships_response::DeletionProgress,
-        ),
-        tonic::Status,
-    > {
+        self,
+    ) -> GrpcResult<(
+        spicedb::ZedToken,
+        spicedb::delete_relationships_response::DeletionProgress,
+    )> {
         let resp = self
-            .client
-            .delete_relationships(self.request)
+            .retry
+            .run("DeleteRelationships", || {
+                let mut client = self.client.clone();
+                let request = self.request.clone();
+                async move { client.delete_relationships(request).await }
+            })
             .await?
             .into_inner();
         let zed_token = resp
             .deleted_at
-            .ok_or_else(|| tonic::Status::internal("Invalid ZedToken"))?;
+            .ok_or_else(|| Error::invalid_response("Invalid ZedToken"))?;
         let deletion_progress = DeletionProgress::try_from(resp.deletion_progress)
-            .map_err(|_| tonic::Status::internal("Invalid i32 value for DeletionProgress"))?;
+            .map_err(|_| Error::invalid_response("Invalid i32 value for DeletionProgress"))?;
+        Session::record(self.session.as_ref(), &zed_token).await?;
         Ok((zed_token, deletion_progress))
     }
 }
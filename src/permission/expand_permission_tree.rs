@@ -0,0 +1,88 @@
+use crate::grpc::GrpcResult;
+use crate::permission::{resolve_consistency, SpiceDBPermissionClient};
+use crate::session::Session;
+use crate::spicedb::object_reference;
+use crate::spicedb::wrappers::{Consistency, ExpandPermissionTreeResponse};
+use crate::{spicedb, Permission, Resource};
+use crate::{Error, RetryPolicy};
+
+#[derive(Clone, Debug)]
+pub struct ExpandPermissionTreeRequest<R> {
+    client: SpiceDBPermissionClient,
+    request: spicedb::ExpandPermissionTreeRequest,
+    retry: RetryPolicy,
+    session: Option<Session>,
+    default_consistency: Option<Consistency>,
+    _phantom: std::marker::PhantomData<R>,
+}
+
+impl<R> ExpandPermissionTreeRequest<R>
+where
+    R: Resource,
+{
+    pub fn new(client: SpiceDBPermissionClient) -> Self {
+        let request = spicedb::ExpandPermissionTreeRequest {
+            ..Default::default()
+        };
+        ExpandPermissionTreeRequest {
+            client,
+            request,
+            retry: RetryPolicy::never(),
+            session: None,
+            default_consistency: None,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Overrides the retry policy of the client for this request.
+    pub fn retry_policy(&mut self, policy: RetryPolicy) -> &mut Self {
+        self.retry = policy;
+        self
+    }
+
+    pub(crate) fn session(&mut self, session: Option<Session>) -> &mut Self {
+        self.session = session;
+        self
+    }
+
+    pub(crate) fn default_consistency(&mut self, consistency: Option<Consistency>) -> &mut Self {
+        self.default_consistency = consistency;
+        self
+    }
+
+    pub fn resource(&mut self, id: impl Into<R::Id>, permission: R::Permissions) -> &mut Self {
+        self.request.resource = Some(object_reference::<R>(id.into()));
+        self.request.permission = permission.name().into();
+        self
+    }
+
+    pub fn with_consistency(&mut self, consistency: Consistency) -> &mut Self {
+        self.request.consistency = Some(consistency.into());
+        self
+    }
+
+    pub async fn send(mut self) -> GrpcResult<ExpandPermissionTreeResponse> {
+        if self.request.resource.is_none() {
+            return Err(Error::invalid_request("resource is required"));
+        }
+        if self.request.permission.is_empty() {
+            return Err(Error::invalid_request("permission is required"));
+        }
+        resolve_consistency(
+            &mut self.request.consistency,
+            self.session.as_ref(),
+            self.default_consistency.take(),
+        )
+        .await?;
+        let resp = self
+            .retry
+            .run("ExpandPermissionTree", || {
+                let mut client = self.client.clone();
+                let request = self.request.clone();
+                async move { client.expand_permission_tree(request).await }
+            })
+            .await?
+            .into_inner();
+        resp.try_into()
+    }
+}
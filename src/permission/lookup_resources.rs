@@ -2,14 +2,19 @@ use futures::TryStreamExt;
 use tokio_stream::{Stream, StreamExt};
 
 use crate::grpc::GrpcResult;
-use crate::permission::SpiceDBPermissionClient;
+use crate::permission::{resolve_consistency, SpiceDBPermissionClient};
+use crate::session::Session;
 use crate::spicedb::wrappers::{Consistency, LookupResourcesResponse};
 use crate::{spicedb, Actor, Permission, Resource};
+use crate::{Error, RetryPolicy};
 
 #[derive(Clone, Debug)]
 pub struct LookupResourcesRequest<R> {
     client: SpiceDBPermissionClient,
     request: spicedb::LookupResourcesRequest,
+    retry: RetryPolicy,
+    session: Option<Session>,
+    default_consistency: Option<Consistency>,
     _phantom: std::marker::PhantomData<R>,
 }
 
@@ -25,10 +30,29 @@ where
         LookupResourcesRequest {
             client,
             request,
+            retry: RetryPolicy::never(),
+            session: None,
+            default_consistency: None,
             _phantom: std::marker::PhantomData,
         }
     }
 
+    /// Overrides the retry policy of the client for this request.
+    pub fn retry_policy(&mut self, policy: RetryPolicy) -> &mut Self {
+        self.retry = policy;
+        self
+    }
+
+    pub(crate) fn session(&mut self, session: Option<Session>) -> &mut Self {
+        self.session = session;
+        self
+    }
+
+    pub(crate) fn default_consistency(&mut self, consistency: Option<Consistency>) -> &mut Self {
+        self.default_consistency = consistency;
+        self
+    }
+
     pub fn permission(&mut self, permission: R::Permissions) -> &mut Self {
         self.request.permission = permission.name().into();
         self
@@ -77,25 +101,39 @@ where
         mut self,
     ) -> GrpcResult<impl Stream<Item = GrpcResult<LookupResourcesResponse<R::Id>>>> {
         if self.request.permission.is_empty() {
-            return Err(tonic::Status::invalid_argument("permission is required"));
+            return Err(Error::invalid_request("permission is required"));
         }
         if self.request.subject.is_none() {
-            return Err(tonic::Status::invalid_argument("subject is required"));
+            return Err(Error::invalid_request("subject is required"));
         }
+        resolve_consistency(
+            &mut self.request.consistency,
+            self.session.as_ref(),
+            self.default_consistency.take(),
+        )
+        .await?;
         let resp = self
-            .client
-            .lookup_resources(self.request)
+            .retry
+            .run("LookupResources", || {
+                let mut client = self.client.clone();
+                let request = self.request.clone();
+                async move { client.lookup_resources(request).await }
+            })
             .await?
             .into_inner();
         Ok(resp.map(|r| {
-            r.and_then(|r| {
-                let id = r.resource_object_id.parse().map_err(|_| {
-                    let expected_type = std::any::type_name::<R::Id>();
-                    tonic::Status::internal(format!(
-                        "Could not parse Id: {} from LookupResourcesResponse, expected a value to be parsed as {}",
-                        r.resource_object_id, expected_type
-                    ))
-                })?;
+            r.map_err(Error::from).and_then(|r| {
+                let id = r
+                    .resource_object_id
+                    .parse()
+                    .map_err(|_| Error::id_parse::<R::Id>(&r.resource_object_id))?;
+                let permissionship = spicedb::LookupPermissionship::try_from(r.permissionship)
+                    .map_err(|_| {
+                        Error::invalid_response(format!(
+                            "Invalid i32 value for LookupPermissionship: {}",
+                            r.permissionship
+                        ))
+                    })?;
                 let missing_caveats = r
                     .partial_caveat_info
                     .map(|p| p.missing_required_context)
@@ -103,8 +141,7 @@ where
                 let response = LookupResourcesResponse::<R::Id> {
                     id,
                     looked_up_at: r.looked_up_at,
-                    permissionship: spicedb::LookupPermissionship::try_from(r.permissionship)
-                        .unwrap(),
+                    permissionship,
                     missing_caveats,
                     after_result_cursor: r.after_result_cursor,
                 };
@@ -1,8 +1,15 @@
+use std::sync::Arc;
+
+use prost::Message;
+
 use crate::grpc::GrpcResult;
-use crate::permission::SpiceDBPermissionClient;
+use crate::permission::{resolve_consistency, SpiceDBPermissionClient};
+use crate::session::Session;
+use crate::single_flight::SingleFlights;
 use crate::spicedb::object_reference;
 use crate::spicedb::wrappers::Consistency;
 use crate::{spicedb, Entity, Permission, Relation, Resource};
+use crate::{Error, RetryPolicy};
 use futures::TryStreamExt;
 use tokio_stream::{Stream, StreamExt};
 
@@ -12,6 +19,10 @@ use self::spicedb::LookupSubjectsResponse;
 pub struct LookupSubjectsRequest<S, R> {
     client: SpiceDBPermissionClient,
     request: spicedb::LookupSubjectsRequest,
+    retry: RetryPolicy,
+    session: Option<Session>,
+    default_consistency: Option<Consistency>,
+    single_flight: Option<Arc<SingleFlights>>,
     _phantom: std::marker::PhantomData<(S, R)>,
 }
 
@@ -28,10 +39,35 @@ where
         LookupSubjectsRequest {
             client,
             request,
+            retry: RetryPolicy::never(),
+            session: None,
+            default_consistency: None,
+            single_flight: None,
             _phantom: std::marker::PhantomData,
         }
     }
 
+    /// Overrides the retry policy of the client for this request.
+    pub fn retry_policy(&mut self, policy: RetryPolicy) -> &mut Self {
+        self.retry = policy;
+        self
+    }
+
+    pub(crate) fn session(&mut self, session: Option<Session>) -> &mut Self {
+        self.session = session;
+        self
+    }
+
+    pub(crate) fn default_consistency(&mut self, consistency: Option<Consistency>) -> &mut Self {
+        self.default_consistency = consistency;
+        self
+    }
+
+    pub(crate) fn single_flight(&mut self, flights: Option<Arc<SingleFlights>>) -> &mut Self {
+        self.single_flight = flights;
+        self
+    }
+
     pub fn with_consistency(&mut self, consistency: Consistency) -> &mut Self {
         self.request.consistency = Some(consistency.into());
         self
@@ -76,54 +112,76 @@ where
         self
     }
 
-    pub async fn send_collect_ids(self) -> GrpcResult<Vec<S::Id>> {
+    pub async fn send_collect_ids(mut self) -> GrpcResult<Vec<S::Id>> {
         if self.request.wildcard_option
             == spicedb::lookup_subjects_request::WildcardOption::IncludeWildcards as i32
         {
-            return Err(tonic::Status::invalid_argument(
+            return Err(Error::invalid_request(
                 "Cannot call send_collect_ids on a lookup_subjects request with wildcards enabled",
             ));
         }
-        self.send_stream()
-            .await?
-            .filter_map(|r| if let Ok(ref resp) = r {
-                if resp.subject.is_none() {
-                    None
-                } else {
-                    Some(r)
-                }
-            } else {
-                Some(r)
-            })
-            .map(|resp| resp.and_then(|r| {
-                let subject = r.subject.unwrap();
-                let id = subject.subject_object_id.parse().map_err(|_| {
-                    let expected_type = std::any::type_name::<S::Id>();
-                    tonic::Status::internal(format!(
-                        "Could not parse Id: {} from LookupResourcesResponse, expected a value to be parsed as {}",
-                        subject.subject_object_id, expected_type
-                    ))
-                })?;
-                Ok(id)
-            }))
-            .try_collect()
-            .await
+        self.prepare().await?;
+        let ids = match self.single_flight {
+            Some(flights) => {
+                let key = self.request.encode_to_vec();
+                let call = lookup_subject_ids(self.client, self.request, self.retry);
+                flights.lookup_subject_ids.run(key, call).await?
+            }
+            None => lookup_subject_ids(self.client, self.request, self.retry).await?,
+        };
+        ids.into_iter()
+            .map(|id| id.parse().map_err(|_| Error::id_parse::<S::Id>(id)))
+            .collect()
     }
 
     pub async fn send_stream(
         mut self,
     ) -> GrpcResult<impl Stream<Item = GrpcResult<LookupSubjectsResponse>>> {
+        self.prepare().await?;
+        lookup_subjects(self.client, self.request, self.retry).await
+    }
+
+    async fn prepare(&mut self) -> GrpcResult<()> {
         if self.request.resource.is_none() {
-            return Err(tonic::Status::invalid_argument("resource is required"));
+            return Err(Error::invalid_request("resource is required"));
         }
         if self.request.permission.is_empty() {
-            return Err(tonic::Status::invalid_argument("permission is required"));
+            return Err(Error::invalid_request("permission is required"));
         }
-        let resp = self
-            .client
-            .lookup_subjects(self.request)
-            .await?
-            .into_inner();
-        Ok(resp)
+        resolve_consistency(
+            &mut self.request.consistency,
+            self.session.as_ref(),
+            self.default_consistency.take(),
+        )
+        .await
     }
 }
+
+async fn lookup_subjects(
+    client: SpiceDBPermissionClient,
+    request: spicedb::LookupSubjectsRequest,
+    retry: RetryPolicy,
+) -> GrpcResult<impl Stream<Item = GrpcResult<LookupSubjectsResponse>>> {
+    let resp = retry
+        .run("LookupSubjects", || {
+            let mut client = client.clone();
+            let request = request.clone();
+            async move { client.lookup_subjects(request).await }
+        })
+        .await?
+        .into_inner();
+    Ok(resp.map(|r| r.map_err(Into::into)))
+}
+
+/// Ids of the subjects found, responses without a subject are skipped
+async fn lookup_subject_ids(
+    client: SpiceDBPermissionClient,
+    request: spicedb::LookupSubjectsRequest,
+    retry: RetryPolicy,
+) -> GrpcResult<Vec<String>> {
+    lookup_subjects(client, request, retry)
+        .await?
+        .try_filter_map(|resp| async move { Ok(resp.subject.map(|s| s.subject_object_id)) })
+        .try_collect()
+        .await
+}
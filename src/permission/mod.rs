@@ -1,19 +1,39 @@
+mod check_bulk_permissions;
 mod check_permission;
 mod delete_relationships;
+mod expand_permission_tree;
 mod lookup_resources;
 mod lookup_subjects;
 mod read_relationships;
 mod write_relationships;
 
-use crate::grpc::AuthenticatedChannel;
+use crate::grpc::{AuthenticatedChannel, GrpcResult};
+use crate::session::Session;
 use crate::spicedb;
+use crate::spicedb::wrappers::Consistency;
 
 pub type SpiceDBPermissionClient =
     spicedb::permissions_service_client::PermissionsServiceClient<AuthenticatedChannel>;
 
+pub use check_bulk_permissions::CheckBulkPermissionsRequest;
 pub use check_permission::CheckPermissionRequest;
 pub use delete_relationships::DeleteRelationshipsRequest;
+pub use expand_permission_tree::ExpandPermissionTreeRequest;
 pub use lookup_resources::LookupResourcesRequest;
 pub use lookup_subjects::LookupSubjectsRequest;
 pub use read_relationships::ReadRelationshipsRequest;
 pub use write_relationships::WriteRelationshipsRequest;
+
+/// Fills in the consistency of a read request that wasn't given one: at least as fresh as the
+/// last write of its session, or else the client's default.
+pub(crate) async fn resolve_consistency(
+    consistency: &mut Option<spicedb::Consistency>,
+    session: Option<&Session>,
+    default: Option<Consistency>,
+) -> GrpcResult<()> {
+    Session::apply(session, consistency).await?;
+    if consistency.is_none() {
+        *consistency = default.map(Into::into);
+    }
+    Ok(())
+}
@@ -1,14 +1,19 @@
 use tokio_stream::{Stream, StreamExt};
 
 use crate::grpc::GrpcResult;
-use crate::permission::SpiceDBPermissionClient;
+use crate::permission::{resolve_consistency, SpiceDBPermissionClient};
+use crate::session::Session;
 use crate::spicedb;
 use crate::spicedb::wrappers::{Consistency, ReadRelationshipsResponse};
+use crate::{Error, RetryPolicy};
 
 #[derive(Clone, Debug)]
 pub struct ReadRelationshipsRequest {
     client: SpiceDBPermissionClient,
     request: spicedb::ReadRelationshipsRequest,
+    retry: RetryPolicy,
+    session: Option<Session>,
+    default_consistency: Option<Consistency>,
 }
 
 impl ReadRelationshipsRequest {
@@ -16,7 +21,29 @@ impl ReadRelationshipsRequest {
         let request = spicedb::ReadRelationshipsRequest {
             ..Default::default()
         };
-        ReadRelationshipsRequest { client, request }
+        ReadRelationshipsRequest {
+            client,
+            request,
+            retry: RetryPolicy::never(),
+            session: None,
+            default_consistency: None,
+        }
+    }
+
+    /// Overrides the retry policy of the client for this request.
+    pub fn retry_policy(&mut self, policy: RetryPolicy) -> &mut Self {
+        self.retry = policy;
+        self
+    }
+
+    pub(crate) fn session(&mut self, session: Option<Session>) -> &mut Self {
+        self.session = session;
+        self
+    }
+
+    pub(crate) fn default_consistency(&mut self, consistency: Option<Consistency>) -> &mut Self {
+        self.default_consistency = consistency;
+        self
     }
 
     pub fn with_limit(&mut self, limit: u32) -> &mut Self {
@@ -42,11 +69,21 @@ impl ReadRelationshipsRequest {
     pub async fn send(
         mut self,
     ) -> GrpcResult<impl Stream<Item = GrpcResult<ReadRelationshipsResponse>>> {
+        resolve_consistency(
+            &mut self.request.consistency,
+            self.session.as_ref(),
+            self.default_consistency.take(),
+        )
+        .await?;
         let resp = self
-            .client
-            .read_relationships(self.request)
+            .retry
+            .run("ReadRelationships", || {
+                let mut client = self.client.clone();
+                let request = self.request.clone();
+                async move { client.read_relationships(request).await }
+            })
             .await?
             .into_inner();
-        Ok(resp.map(|r| r.map(Into::into)))
+        Ok(resp.map(|r| r.map_err(Error::from).and_then(TryInto::try_into)))
     }
 }
@@ -1,7 +1,9 @@
 use crate::grpc::GrpcResult;
 use crate::permission::SpiceDBPermissionClient;
+use crate::session::Session;
 use crate::spicedb::{caveated_relationship_update, wildcard_relationship_update};
 use crate::{spicedb, Caveat, Entity, RelationshipOperation, Resource};
+use crate::{Error, RetryPolicy};
 
 use self::spicedb::precondition::Operation;
 use self::spicedb::{relationship_update, Precondition};
@@ -10,6 +12,8 @@ use self::spicedb::{relationship_update, Precondition};
 pub struct WriteRelationshipsRequest {
     client: SpiceDBPermissionClient,
     request: spicedb::WriteRelationshipsRequest,
+    retry: RetryPolicy,
+    session: Option<Session>,
 }
 
 impl WriteRelationshipsRequest {
@@ -18,7 +22,23 @@ impl WriteRelationshipsRequest {
             updates: vec![],
             optional_preconditions: vec![],
         };
-        WriteRelationshipsRequest { client, request }
+        WriteRelationshipsRequest {
+            client,
+            request,
+            retry: RetryPolicy::never(),
+            session: None,
+        }
+    }
+
+    /// Overrides the retry policy of the client for this request.
+    pub fn retry_policy(&mut self, policy: RetryPolicy) -> &mut Self {
+        self.retry = policy;
+        self
+    }
+
+    pub(crate) fn session(&mut self, session: Option<Session>) -> &mut Self {
+        self.session = session;
+        self
     }
 
     pub fn add_precondition_raw(&mut self, precondition: Precondition) -> &mut Self {
@@ -122,13 +142,29 @@ impl WriteRelationshipsRequest {
         self
     }
 
+    /// Only retried if all updates are `Touch` or `Delete`, a `Create` would fail on the retry of
+    /// a write that succeeded without the response reaching us.
     pub async fn send(mut self) -> GrpcResult<spicedb::ZedToken> {
+        let idempotent = self.request.updates.iter().all(|update| {
+            update.operation == RelationshipOperation::Touch as i32
+                || update.operation == RelationshipOperation::Delete as i32
+        });
+        if !idempotent {
+            self.retry = RetryPolicy::never();
+        }
         let resp = self
-            .client
-            .write_relationships(self.request)
+            .retry
+            .run("WriteRelationships", || {
+                let mut client = self.client.clone();
+                let request = self.request.clone();
+                async move { client.write_relationships(request).await }
+            })
             .await?
             .into_inner();
-        resp.written_at
-            .ok_or_else(|| tonic::Status::internal("Invalid ZedToken"))
+        let token = resp
+            .written_at
+            .ok_or_else(|| Error::invalid_response("Invalid ZedToken"))?;
+        Session::record(self.session.as_ref(), &token).await?;
+        Ok(token)
     }
 }
@@ -0,0 +1,192 @@
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::grpc::GrpcResult;
+use crate::spicedb::ErrorReason;
+use crate::Error;
+
+type RetryHook = Arc<dyn Fn(&RetryEvent<'_>) + Send + Sync>;
+
+/// Passed to the `on_retry` hook before waiting for the next attempt
+#[derive(Debug)]
+pub struct RetryEvent<'a> {
+    /// gRPC method of the request, e.g. `CheckPermission`
+    pub operation: &'static str,
+    /// The attempt that failed, starting at 1
+    pub attempt: u32,
+    pub delay: Duration,
+    pub error: &'a Error,
+}
+
+/// Retries idempotent requests failing with a transient error, i.e. `Unavailable`,
+/// `DeadlineExceeded`, `ERROR_REASON_SERIALIZATION_FAILURE` and
+/// `ERROR_REASON_INMEMORY_TOO_MANY_CONCURRENT_UPDATES`.
+///
+/// Applies to checks, lookups, `ReadRelationships`, `ReadSchema` and writes whose updates are
+/// all `Touch` or `Delete`. Streaming requests are only retried until the stream is established.
+/// The delay before attempt `n + 1` is `initial_backoff * multiplier^(n - 1)`, capped at
+/// `max_backoff` and reduced by a random fraction of up to `jitter`.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: f64,
+    on_retry: Option<RetryHook>,
+}
+
+impl RetryPolicy {
+    /// Sends every request once, the policy of clients that weren't given one.
+    pub fn never() -> Self {
+        let mut policy = Self::default();
+        policy.max_attempts(1);
+        policy
+    }
+
+    /// Attempts including the first one, at least 1.
+    pub fn max_attempts(&mut self, attempts: u32) -> &mut Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    pub fn initial_backoff(&mut self, backoff: Duration) -> &mut Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    pub fn max_backoff(&mut self, backoff: Duration) -> &mut Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    pub fn multiplier(&mut self, multiplier: f64) -> &mut Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Fraction of the delay that is randomized, between 0 (no jitter) and 1 (full jitter).
+    pub fn jitter(&mut self, jitter: f64) -> &mut Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Called before every retry, e.g. to log or count them.
+    pub fn on_retry(
+        &mut self,
+        hook: impl Fn(&RetryEvent<'_>) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.on_retry = Some(Arc::new(hook));
+        self
+    }
+
+    /// Whether both policies retry the same way, hooks are compared by identity.
+    pub(crate) fn same_as(&self, other: &RetryPolicy) -> bool {
+        let same_hook = match (&self.on_retry, &other.on_retry) {
+            (Some(hook), Some(other)) => Arc::ptr_eq(hook, other),
+            (hook, other) => hook.is_none() && other.is_none(),
+        };
+        self.max_attempts == other.max_attempts
+            && self.initial_backoff == other.initial_backoff
+            && self.max_backoff == other.max_backoff
+            && self.multiplier == other.multiplier
+            && self.jitter == other.jitter
+            && same_hook
+    }
+
+    fn delay(&self, attempt: u32) -> Duration {
+        let exponent = i32::try_from(attempt - 1).unwrap_or(i32::MAX);
+        let backoff = self
+            .initial_backoff
+            .mul_f64(self.multiplier.powi(exponent).min(u32::MAX.into()))
+            .min(self.max_backoff);
+        backoff.mul_f64(1.0 - self.jitter * random_fraction())
+    }
+
+    /// Runs `send` until it succeeds, fails with a permanent error or runs out of attempts.
+    pub(crate) async fn run<T, F, Fut>(&self, operation: &'static str, mut send: F) -> GrpcResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, tonic::Status>>,
+    {
+        let mut attempt = 1;
+        loop {
+            let err = match send().await {
+                Ok(resp) => return Ok(resp),
+                Err(status) => Error::from(status),
+            };
+            if attempt >= self.max_attempts || !is_transient(&err) {
+                return Err(err);
+            }
+            let delay = self.delay(attempt);
+            if let Some(hook) = &self.on_retry {
+                hook(&RetryEvent {
+                    operation,
+                    attempt,
+                    delay,
+                    error: &err,
+                });
+            }
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// 3 attempts, starting at 50ms and doubling up to 1s, with 50% jitter
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(1),
+            multiplier: 2.0,
+            jitter: 0.5,
+            on_retry: None,
+        }
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("multiplier", &self.multiplier)
+            .field("jitter", &self.jitter)
+            .field("on_retry", &self.on_retry.is_some())
+            .finish()
+    }
+}
+
+fn is_transient(err: &Error) -> bool {
+    match err {
+        Error::Transport(_) => true,
+        Error::Status {
+            reason:
+                Some(
+                    ErrorReason::SerializationFailure
+                    | ErrorReason::InmemoryTooManyConcurrentUpdates,
+                ),
+            ..
+        } => true,
+        _ => {
+            err.status().is_some()
+                && matches!(
+                    err.code(),
+                    tonic::Code::Unavailable | tonic::Code::DeadlineExceeded
+                )
+        }
+    }
+}
+
+/// Random number in `[0, 1)`, good enough for jitter without pulling in `rand`
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
@@ -0,0 +1,386 @@
+//! Typed syntax tree of the SpiceDB schema language, as produced by `parse_schema`.
+//!
+//! The `Display` impls print a tree back as schema text in a canonical layout, printing a parsed
+//! schema and parsing the output again yields the same tree apart from the spans.
+
+use std::fmt;
+
+/// Byte range of a node in the parsed schema text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// Smallest span covering both `self` and `other`
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Schema {
+    pub items: Vec<Item>,
+}
+
+impl Schema {
+    pub fn definitions(&self) -> impl Iterator<Item = &Definition> {
+        self.items.iter().filter_map(|item| match item {
+            Item::Definition(definition) => Some(definition),
+            _ => None,
+        })
+    }
+
+    pub fn caveats(&self) -> impl Iterator<Item = &Caveat> {
+        self.items.iter().filter_map(|item| match item {
+            Item::Caveat(caveat) => Some(caveat),
+            _ => None,
+        })
+    }
+
+    pub fn definition(&self, name: &str) -> Option<&Definition> {
+        self.definitions().find(|d| d.name == name)
+    }
+
+    pub fn caveat(&self, name: &str) -> Option<&Caveat> {
+        self.caveats().find(|c| c.name == name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Item {
+    Use(UseDirective),
+    Definition(Definition),
+    Caveat(Caveat),
+}
+
+/// Opt-in to a language feature, `use expiration`
+#[derive(Clone, Debug, PartialEq)]
+pub struct UseDirective {
+    pub feature: String,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Definition {
+    pub name: String,
+    /// Comments directly preceding the definition, including their delimiter characters
+    pub comments: Vec<String>,
+    pub members: Vec<Member>,
+    pub span: Span,
+}
+
+impl Definition {
+    pub fn relations(&self) -> impl Iterator<Item = &Relation> {
+        self.members.iter().filter_map(|member| match member {
+            Member::Relation(relation) => Some(relation),
+            _ => None,
+        })
+    }
+
+    pub fn permissions(&self) -> impl Iterator<Item = &Permission> {
+        self.members.iter().filter_map(|member| match member {
+            Member::Permission(permission) => Some(permission),
+            _ => None,
+        })
+    }
+
+    pub fn relation(&self, name: &str) -> Option<&Relation> {
+        self.relations().find(|r| r.name == name)
+    }
+
+    pub fn permission(&self, name: &str) -> Option<&Permission> {
+        self.permissions().find(|p| p.name == name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Member {
+    Relation(Relation),
+    Permission(Permission),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Relation {
+    pub name: String,
+    pub comments: Vec<String>,
+    pub subject_types: Vec<SubjectType>,
+    pub span: Span,
+}
+
+/// One of the allowed subject types of a relation, e.g. `user`, `group#member` or `user:*`,
+/// optionally `with` a caveat and/or `expiration`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubjectType {
+    pub definition: String,
+    pub kind: SubjectTypeKind,
+    pub caveat: Option<String>,
+    pub expiration: bool,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SubjectTypeKind {
+    /// The subject is referenced directly, `user`
+    Terminal,
+    /// The subject is referenced through one of its relations, `group#member`
+    Relation(String),
+    /// Any subject of the definition, `user:*`
+    Wildcard,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Permission {
+    pub name: String,
+    pub comments: Vec<String>,
+    pub expression: Expr,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+/// Permission expression. Arrows bind tightest, followed by `+`, `&` and `-`, all binary
+/// operators are left associative.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExprKind {
+    Nil,
+    /// A relation or permission of the same definition
+    Reference(String),
+    /// `relation->permission`, the permission on the subjects of the relation
+    Arrow {
+        relation: String,
+        permission: String,
+    },
+    Union(Box<Expr>, Box<Expr>),
+    Intersection(Box<Expr>, Box<Expr>),
+    Exclusion(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// Names of the relations and permissions referenced by the expression, for arrows only the
+    /// relation is included since the permission belongs to another definition.
+    pub fn references(&self) -> Vec<&str> {
+        let mut references = vec![];
+        self.collect_references(&mut references);
+        references
+    }
+
+    fn collect_references<'a>(&'a self, references: &mut Vec<&'a str>) {
+        match &self.kind {
+            ExprKind::Nil => {}
+            ExprKind::Reference(name) => references.push(name),
+            ExprKind::Arrow { relation, .. } => references.push(relation),
+            ExprKind::Union(left, right)
+            | ExprKind::Intersection(left, right)
+            | ExprKind::Exclusion(left, right) => {
+                left.collect_references(references);
+                right.collect_references(references);
+            }
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self.kind {
+            ExprKind::Exclusion(..) => 1,
+            ExprKind::Intersection(..) => 2,
+            ExprKind::Union(..) => 3,
+            _ => 4,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Caveat {
+    pub name: String,
+    pub comments: Vec<String>,
+    pub parameters: Vec<CaveatParameter>,
+    /// CEL expression between the braces, leading and trailing whitespace trimmed
+    pub expression: String,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CaveatParameter {
+    pub name: String,
+    pub parameter_type: CaveatType,
+    pub span: Span,
+}
+
+/// Parameter type of a caveat, e.g. `int` or `map<list<string>>`
+#[derive(Clone, Debug, PartialEq)]
+pub struct CaveatType {
+    pub name: String,
+    pub type_args: Vec<CaveatType>,
+}
+
+const INDENT: &str = "    ";
+
+fn write_comments(f: &mut fmt::Formatter<'_>, comments: &[String], indent: &str) -> fmt::Result {
+    for comment in comments {
+        writeln!(f, "{}{}", indent, comment)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Schema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, item) in self.items.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            match item {
+                Item::Use(directive) => writeln!(f, "{}", directive)?,
+                Item::Definition(definition) => writeln!(f, "{}", definition)?,
+                Item::Caveat(caveat) => writeln!(f, "{}", caveat)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for UseDirective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "use {}", self.feature)
+    }
+}
+
+impl fmt::Display for Definition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_comments(f, &self.comments, "")?;
+        if self.members.is_empty() {
+            return write!(f, "definition {} {{}}", self.name);
+        }
+        writeln!(f, "definition {} {{", self.name)?;
+        let mut previous: Option<&Member> = None;
+        for member in &self.members {
+            // Relations and permissions are grouped in blocks separated by a blank line
+            let kind_changed = matches!(
+                (previous, member),
+                (Some(Member::Relation(_)), Member::Permission(_))
+                    | (Some(Member::Permission(_)), Member::Relation(_))
+            );
+            if kind_changed {
+                writeln!(f)?;
+            }
+            match member {
+                Member::Relation(relation) => {
+                    write_comments(f, &relation.comments, INDENT)?;
+                    writeln!(f, "{}{}", INDENT, relation)?;
+                }
+                Member::Permission(permission) => {
+                    write_comments(f, &permission.comments, INDENT)?;
+                    writeln!(f, "{}{}", INDENT, permission)?;
+                }
+            }
+            previous = Some(member);
+        }
+        write!(f, "}}")
+    }
+}
+
+impl fmt::Display for Relation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "relation {}: ", self.name)?;
+        for (i, subject_type) in self.subject_types.iter().enumerate() {
+            if i > 0 {
+                write!(f, " | ")?;
+            }
+            write!(f, "{}", subject_type)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SubjectType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.definition)?;
+        match &self.kind {
+            SubjectTypeKind::Terminal => {}
+            SubjectTypeKind::Relation(relation) => write!(f, "#{}", relation)?,
+            SubjectTypeKind::Wildcard => write!(f, ":*")?,
+        }
+        match (&self.caveat, self.expiration) {
+            (Some(caveat), true) => write!(f, " with {} and expiration", caveat),
+            (Some(caveat), false) => write!(f, " with {}", caveat),
+            (None, true) => write!(f, " with expiration"),
+            (None, false) => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "permission {} = {}", self.name, self.expression)
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (op, left, right) = match &self.kind {
+            ExprKind::Nil => return write!(f, "nil"),
+            ExprKind::Reference(name) => return write!(f, "{}", name),
+            ExprKind::Arrow {
+                relation,
+                permission,
+            } => return write!(f, "{}->{}", relation, permission),
+            ExprKind::Union(left, right) => ("+", left, right),
+            ExprKind::Intersection(left, right) => ("&", left, right),
+            ExprKind::Exclusion(left, right) => ("-", left, right),
+        };
+        // Operators are left associative, so a right operand of the same precedence needs
+        // parentheses to keep the shape of the tree
+        if left.precedence() < self.precedence() {
+            write!(f, "({})", left)?;
+        } else {
+            write!(f, "{}", left)?;
+        }
+        write!(f, " {} ", op)?;
+        if right.precedence() <= self.precedence() {
+            write!(f, "({})", right)
+        } else {
+            write!(f, "{}", right)
+        }
+    }
+}
+
+impl fmt::Display for Caveat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_comments(f, &self.comments, "")?;
+        write!(f, "caveat {}(", self.name)?;
+        for (i, parameter) in self.parameters.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{} {}", parameter.name, parameter.parameter_type)?;
+        }
+        writeln!(f, ") {{")?;
+        writeln!(f, "{}{}", INDENT, self.expression)?;
+        write!(f, "}}")
+    }
+}
+
+impl fmt::Display for CaveatType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.type_args.is_empty() {
+            write!(f, "<")?;
+            for (i, arg) in self.type_args.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", arg)?;
+            }
+            write!(f, ">")?;
+        }
+        Ok(())
+    }
+}
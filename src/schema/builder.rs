@@ -0,0 +1,427 @@
+//! Schema-as-code, building a schema from the `Entity` types instead of maintaining a `.zed` file.
+//!
+//! ```ignore
+//! let schema = SchemaBuilder::new()
+//!     .definition::<User>(|d| d)
+//!     .definition::<Document>(|d| {
+//!         d.relation(DocumentRelation::Reader, [subject::<User>(), wildcard::<User>()])
+//!             .relation(DocumentRelation::Writer, [subject::<User>()])
+//!             .permission(
+//!                 DocumentPermission::Read,
+//!                 rel(DocumentRelation::Reader) + rel(DocumentRelation::Writer),
+//!             )
+//!     })
+//!     .render()?;
+//! client.write_schema(schema).await?;
+//! ```
+
+use std::collections::HashSet;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Add, BitAnd, Sub};
+
+use super::ast::{
+    self, CaveatParameter, Expr, ExprKind, Item, Member, Span, SubjectType, SubjectTypeKind,
+};
+use super::ParseError;
+use crate::entity::{Caveat, Entity, Permission, Relation, Resource};
+
+/// Permission expression of a definition with the relations `R` and permissions `P`, built
+/// from `rel`, `perm`, `arrow` and `nil` and combined with `+` (union), `&` (intersection) and `-`
+/// (exclusion). Operands are grouped by Rust's precedence, `a - b & c` is `(a - b) & c`.
+///
+/// Referencing the enum variants instead of names makes renaming a relation or permission a
+/// compile error.
+pub struct PermissionExpr<R, P> {
+    expr: Expr,
+    _phantom: PhantomData<fn() -> (R, P)>,
+}
+
+impl<R, P> PermissionExpr<R, P> {
+    fn new(kind: ExprKind) -> Self {
+        PermissionExpr {
+            expr: Expr {
+                kind,
+                span: Span::default(),
+            },
+            _phantom: PhantomData,
+        }
+    }
+
+    fn combine(self, other: Self, kind: fn(Box<Expr>, Box<Expr>) -> ExprKind) -> Self {
+        Self::new(kind(Box::new(self.expr), Box::new(other.expr)))
+    }
+}
+
+impl<R, P> From<PermissionExpr<R, P>> for Expr {
+    fn from(expr: PermissionExpr<R, P>) -> Self {
+        expr.expr
+    }
+}
+
+impl<R, P> Add for PermissionExpr<R, P> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.combine(other, ExprKind::Union)
+    }
+}
+
+impl<R, P> BitAnd for PermissionExpr<R, P> {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self {
+        self.combine(other, ExprKind::Intersection)
+    }
+}
+
+impl<R, P> Sub for PermissionExpr<R, P> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.combine(other, ExprKind::Exclusion)
+    }
+}
+
+/// A relation of the definition, `reader`
+pub fn rel<R: Relation, P>(relation: R) -> PermissionExpr<R, P> {
+    PermissionExpr::new(ExprKind::Reference(relation.name().to_owned()))
+}
+
+/// Another permission of the definition, `read`
+pub fn perm<R, P: Permission>(permission: P) -> PermissionExpr<R, P> {
+    PermissionExpr::new(ExprKind::Reference(permission.name().to_owned()))
+}
+
+/// `relation->permission`, the permission or relation `target` of the subjects of `relation`,
+/// e.g. `arrow(DocumentRelation::Parent, FolderPermission::View)`
+pub fn arrow<R: Relation, P>(relation: R, target: impl Permission) -> PermissionExpr<R, P> {
+    PermissionExpr::new(ExprKind::Arrow {
+        relation: relation.name().to_owned(),
+        permission: target.name().to_owned(),
+    })
+}
+
+/// `nil`, the empty set of subjects
+pub fn nil<R, P>() -> PermissionExpr<R, P> {
+    PermissionExpr::new(ExprKind::Nil)
+}
+
+/// Subject type referencing the entity directly, `user`
+pub fn subject<E: Entity>() -> SubjectType {
+    subject_type(E::object_type(), SubjectTypeKind::Terminal)
+}
+
+/// Subject type referencing any entity of the type, `user:*`
+pub fn wildcard<E: Entity>() -> SubjectType {
+    subject_type(E::object_type(), SubjectTypeKind::Wildcard)
+}
+
+/// Subject type referencing the subjects of a relation of the entity, `group#member`
+pub fn subject_relation<E: Entity>(relation: E::Relations) -> SubjectType {
+    subject_type(
+        E::object_type(),
+        SubjectTypeKind::Relation(relation.name().to_owned()),
+    )
+}
+
+fn subject_type(definition: &str, kind: SubjectTypeKind) -> SubjectType {
+    SubjectType {
+        definition: definition.to_owned(),
+        kind,
+        caveat: None,
+        expiration: false,
+        span: Span::default(),
+    }
+}
+
+impl SubjectType {
+    /// Only allow relationships with this subject type when they are caveated with `C`
+    pub fn with_caveat<C: Caveat>(mut self) -> Self {
+        self.caveat = Some(C::name().to_owned());
+        self
+    }
+
+    /// Allow relationships with this subject type to expire, requires `use expiration`
+    pub fn with_expiration(mut self) -> Self {
+        self.expiration = true;
+        self
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SchemaBuilder {
+    items: Vec<Item>,
+    /// First malformed parameter type, returned by `build`
+    error: Option<SchemaBuildError>,
+}
+
+impl SchemaBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Opt-in to a language feature, e.g. `expiration`
+    pub fn use_feature(mut self, feature: &str) -> Self {
+        self.items.push(Item::Use(ast::UseDirective {
+            feature: feature.to_owned(),
+            span: Span::default(),
+        }));
+        self
+    }
+
+    pub fn definition<E: Entity>(
+        mut self,
+        build: impl FnOnce(DefinitionBuilder<E>) -> DefinitionBuilder<E>,
+    ) -> Self {
+        let builder = build(DefinitionBuilder {
+            definition: ast::Definition {
+                name: E::object_type().to_owned(),
+                comments: vec![],
+                members: vec![],
+                span: Span::default(),
+            },
+            _phantom: PhantomData,
+        });
+        self.items.push(Item::Definition(builder.definition));
+        self
+    }
+
+    /// Adds the caveat `C` with its parameters as `(name, type)` pairs and CEL expression.
+    /// Malformed parameter types are reported by `build`.
+    pub fn caveat<C: Caveat>(
+        mut self,
+        parameters: impl IntoIterator<Item = (&'static str, &'static str)>,
+        expression: &str,
+    ) -> Self {
+        let mut parsed = vec![];
+        for (name, parameter_type) in parameters {
+            match super::parse_caveat_type(parameter_type) {
+                Ok(parameter_type) => parsed.push(CaveatParameter {
+                    name: name.to_owned(),
+                    parameter_type,
+                    span: Span::default(),
+                }),
+                Err(error) => {
+                    self.error
+                        .get_or_insert(SchemaBuildError::InvalidParameterType {
+                            caveat: C::name().to_owned(),
+                            parameter: name.to_owned(),
+                            error,
+                        });
+                }
+            }
+        }
+        self.items.push(Item::Caveat(ast::Caveat {
+            name: C::name().to_owned(),
+            comments: vec![],
+            parameters: parsed,
+            expression: expression.trim().to_owned(),
+            span: Span::default(),
+        }));
+        self
+    }
+
+    /// Checks that all parameter types are well formed and all names referenced
+    /// by subject types and permission expressions exist, and returns the syntax tree of the
+    /// schema.
+    pub fn build(self) -> Result<ast::Schema, SchemaBuildError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let schema = ast::Schema { items: self.items };
+        let mut definitions = HashSet::new();
+        for definition in schema.definitions() {
+            if !definitions.insert(&definition.name) {
+                return Err(SchemaBuildError::DuplicateDefinition(
+                    definition.name.clone(),
+                ));
+            }
+        }
+        for definition in schema.definitions() {
+            for relation in definition.relations() {
+                for subject_type in &relation.subject_types {
+                    if !definitions.contains(&subject_type.definition) {
+                        return Err(SchemaBuildError::UnknownDefinition {
+                            definition: definition.name.clone(),
+                            relation: relation.name.clone(),
+                            subject: subject_type.definition.clone(),
+                        });
+                    }
+                    if let Some(caveat) = &subject_type.caveat {
+                        if schema.caveat(caveat).is_none() {
+                            return Err(SchemaBuildError::UnknownCaveat {
+                                definition: definition.name.clone(),
+                                relation: relation.name.clone(),
+                                caveat: caveat.clone(),
+                            });
+                        }
+                    }
+                }
+            }
+            for permission in definition.permissions() {
+                check_references(definition, &permission.name, &permission.expression)?;
+            }
+        }
+        Ok(schema)
+    }
+
+    /// Builds the schema and renders it as Zed text for `write_schema`.
+    pub fn render(self) -> Result<String, SchemaBuildError> {
+        Ok(self.build()?.to_string())
+    }
+}
+
+fn check_references(
+    definition: &ast::Definition,
+    permission: &str,
+    expr: &Expr,
+) -> Result<(), SchemaBuildError> {
+    let unknown = |name: &str| SchemaBuildError::UnknownReference {
+        definition: definition.name.clone(),
+        permission: permission.to_owned(),
+        name: name.to_owned(),
+    };
+    match &expr.kind {
+        ExprKind::Nil => Ok(()),
+        ExprKind::Reference(name) => {
+            if definition.relation(name).is_none() && definition.permission(name).is_none() {
+                return Err(unknown(name));
+            }
+            Ok(())
+        }
+        ExprKind::Arrow { relation, .. } => {
+            if definition.relation(relation).is_none() {
+                return Err(unknown(relation));
+            }
+            Ok(())
+        }
+        ExprKind::Union(left, right)
+        | ExprKind::Intersection(left, right)
+        | ExprKind::Exclusion(left, right) => {
+            check_references(definition, permission, left)?;
+            check_references(definition, permission, right)
+        }
+    }
+}
+
+pub struct DefinitionBuilder<E> {
+    definition: ast::Definition,
+    _phantom: PhantomData<E>,
+}
+
+impl<E: Entity> DefinitionBuilder<E> {
+    /// Adds a doc comment, e.g. `/** a document */`
+    pub fn comment(mut self, comment: &str) -> Self {
+        self.definition.comments.push(comment.to_owned());
+        self
+    }
+
+    pub fn relation(
+        mut self,
+        relation: E::Relations,
+        subject_types: impl IntoIterator<Item = SubjectType>,
+    ) -> Self {
+        self.definition
+            .members
+            .push(Member::Relation(ast::Relation {
+                name: relation.name().to_owned(),
+                comments: vec![],
+                subject_types: subject_types.into_iter().collect(),
+                span: Span::default(),
+            }));
+        self
+    }
+}
+
+impl<E: Resource> DefinitionBuilder<E> {
+    pub fn permission(
+        mut self,
+        permission: E::Permissions,
+        expression: PermissionExpr<E::Relations, E::Permissions>,
+    ) -> Self {
+        self.definition
+            .members
+            .push(Member::Permission(ast::Permission {
+                name: permission.name().to_owned(),
+                comments: vec![],
+                expression: expression.into(),
+                span: Span::default(),
+            }));
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SchemaBuildError {
+    DuplicateDefinition(String),
+    UnknownDefinition {
+        definition: String,
+        relation: String,
+        subject: String,
+    },
+    UnknownCaveat {
+        definition: String,
+        relation: String,
+        caveat: String,
+    },
+    /// A permission expression references a relation or permission not in the definition
+    UnknownReference {
+        definition: String,
+        permission: String,
+        name: String,
+    },
+    InvalidParameterType {
+        caveat: String,
+        parameter: String,
+        error: ParseError,
+    },
+}
+
+impl fmt::Display for SchemaBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaBuildError::DuplicateDefinition(name) => {
+                write!(f, "definition `{}` is defined more than once", name)
+            }
+            SchemaBuildError::UnknownDefinition {
+                definition,
+                relation,
+                subject,
+            } => write!(
+                f,
+                "relation `{}#{}` allows subjects of `{}` which is not defined",
+                definition, relation, subject
+            ),
+            SchemaBuildError::UnknownCaveat {
+                definition,
+                relation,
+                caveat,
+            } => write!(
+                f,
+                "relation `{}#{}` uses caveat `{}` which is not defined",
+                definition, relation, caveat
+            ),
+            SchemaBuildError::UnknownReference {
+                definition,
+                permission,
+                name,
+            } => write!(
+                f,
+                "permission `{}#{}` references `{}` which is not defined",
+                definition, permission, name
+            ),
+            SchemaBuildError::InvalidParameterType {
+                caveat,
+                parameter,
+                error,
+            } => write!(
+                f,
+                "parameter `{}` of caveat `{}` has an invalid type: {}",
+                parameter, caveat, error
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SchemaBuildError {}
@@ -0,0 +1,433 @@
+//! Reviewable schema deploys: `plan_schema_change` diffs a new schema against the deployed one
+//! with `ExperimentalDiffSchema`, `apply_schema_change` writes it if the `SchemaChangePolicy`
+//! allows every destructive change of the plan.
+
+use std::fmt;
+
+use tokio_stream::StreamExt;
+
+use super::{CaveatDefinition, CaveatParameter, Definition, PermissionDefinition};
+use super::{RelationDefinition, SubjectType, SubjectTypeKind};
+use crate::grpc::GrpcResult;
+use crate::spicedb::exp_schema_diff::Diff;
+use crate::spicedb::wrappers::Consistency;
+use crate::Error;
+use crate::{spicedb, SpiceDBClient};
+
+/// A single difference between the deployed and the planned schema.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SchemaChange {
+    DefinitionAdded(Definition),
+    DefinitionRemoved(Definition),
+    DefinitionCommentChanged(Definition),
+    RelationAdded {
+        definition: String,
+        relation: RelationDefinition,
+    },
+    RelationRemoved {
+        definition: String,
+        relation: RelationDefinition,
+    },
+    RelationCommentChanged {
+        definition: String,
+        relation: RelationDefinition,
+    },
+    SubjectTypeAdded {
+        definition: String,
+        relation: String,
+        subject_type: SubjectType,
+    },
+    SubjectTypeRemoved {
+        definition: String,
+        relation: String,
+        subject_type: SubjectType,
+    },
+    PermissionAdded {
+        definition: String,
+        permission: PermissionDefinition,
+    },
+    PermissionRemoved {
+        definition: String,
+        permission: PermissionDefinition,
+    },
+    PermissionCommentChanged {
+        definition: String,
+        permission: PermissionDefinition,
+    },
+    PermissionExpressionChanged {
+        definition: String,
+        permission: PermissionDefinition,
+    },
+    CaveatAdded(CaveatDefinition),
+    CaveatRemoved(CaveatDefinition),
+    CaveatCommentChanged(CaveatDefinition),
+    CaveatExpressionChanged(CaveatDefinition),
+    CaveatParameterAdded {
+        caveat: String,
+        parameter: CaveatParameter,
+    },
+    CaveatParameterRemoved {
+        caveat: String,
+        parameter: CaveatParameter,
+    },
+    CaveatParameterTypeChanged {
+        caveat: String,
+        parameter: CaveatParameter,
+        previous_type: String,
+    },
+}
+
+impl SchemaChange {
+    /// Whether the change can invalidate stored relationships or break callers, i.e. removals
+    /// and incompatible caveat changes.
+    pub fn is_destructive(&self) -> bool {
+        matches!(
+            self,
+            SchemaChange::DefinitionRemoved(_)
+                | SchemaChange::RelationRemoved { .. }
+                | SchemaChange::SubjectTypeRemoved { .. }
+                | SchemaChange::PermissionRemoved { .. }
+                | SchemaChange::CaveatRemoved(_)
+                | SchemaChange::CaveatParameterRemoved { .. }
+                | SchemaChange::CaveatParameterTypeChanged { .. }
+        )
+    }
+
+    /// Filter matching the stored relationships affected by a destructive change. `None` if the
+    /// change never affects relationships, e.g. removing a permission.
+    fn affected_relationships(&self) -> Option<spicedb::RelationshipFilter> {
+        match self {
+            SchemaChange::DefinitionRemoved(definition) => Some(spicedb::RelationshipFilter {
+                resource_type: definition.name.clone(),
+                ..Default::default()
+            }),
+            SchemaChange::RelationRemoved {
+                definition,
+                relation,
+            } => Some(spicedb::RelationshipFilter {
+                resource_type: definition.clone(),
+                optional_relation: relation.name.clone(),
+                ..Default::default()
+            }),
+            SchemaChange::SubjectTypeRemoved {
+                definition,
+                relation,
+                subject_type,
+            } => {
+                let (subject_id, subject_relation) = match &subject_type.kind {
+                    SubjectTypeKind::Terminal => (String::new(), String::new()),
+                    SubjectTypeKind::Relation(relation) => (String::new(), relation.clone()),
+                    SubjectTypeKind::Wildcard => ("*".to_owned(), String::new()),
+                };
+                Some(spicedb::RelationshipFilter {
+                    resource_type: definition.clone(),
+                    optional_relation: relation.clone(),
+                    optional_subject_filter: Some(spicedb::SubjectFilter {
+                        subject_type: subject_type.definition.clone(),
+                        optional_subject_id: subject_id,
+                        optional_relation: Some(spicedb::subject_filter::RelationFilter {
+                            relation: subject_relation,
+                        }),
+                    }),
+                    ..Default::default()
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaChange::DefinitionAdded(d) => write!(f, "+ definition {}", d.name),
+            SchemaChange::DefinitionRemoved(d) => write!(f, "- definition {}", d.name),
+            SchemaChange::DefinitionCommentChanged(d) => {
+                write!(f, "~ definition {} comment", d.name)
+            }
+            SchemaChange::RelationAdded {
+                definition,
+                relation,
+            } => write!(f, "+ relation {}#{}", definition, relation.name),
+            SchemaChange::RelationRemoved {
+                definition,
+                relation,
+            } => write!(f, "- relation {}#{}", definition, relation.name),
+            SchemaChange::RelationCommentChanged {
+                definition,
+                relation,
+            } => write!(f, "~ relation {}#{} comment", definition, relation.name),
+            SchemaChange::SubjectTypeAdded {
+                definition,
+                relation,
+                subject_type,
+            } => write!(
+                f,
+                "+ subject type {} on relation {}#{}",
+                subject_type, definition, relation
+            ),
+            SchemaChange::SubjectTypeRemoved {
+                definition,
+                relation,
+                subject_type,
+            } => write!(
+                f,
+                "- subject type {} on relation {}#{}",
+                subject_type, definition, relation
+            ),
+            SchemaChange::PermissionAdded {
+                definition,
+                permission,
+            } => write!(f, "+ permission {}#{}", definition, permission.name),
+            SchemaChange::PermissionRemoved {
+                definition,
+                permission,
+            } => write!(f, "- permission {}#{}", definition, permission.name),
+            SchemaChange::PermissionCommentChanged {
+                definition,
+                permission,
+            } => write!(f, "~ permission {}#{} comment", definition, permission.name),
+            SchemaChange::PermissionExpressionChanged {
+                definition,
+                permission,
+            } => write!(
+                f,
+                "~ permission {}#{} expression",
+                definition, permission.name
+            ),
+            SchemaChange::CaveatAdded(c) => write!(f, "+ caveat {}", c.name),
+            SchemaChange::CaveatRemoved(c) => write!(f, "- caveat {}", c.name),
+            SchemaChange::CaveatCommentChanged(c) => write!(f, "~ caveat {} comment", c.name),
+            SchemaChange::CaveatExpressionChanged(c) => {
+                write!(f, "~ caveat {} expression", c.name)
+            }
+            SchemaChange::CaveatParameterAdded { caveat, parameter } => write!(
+                f,
+                "+ parameter {} {} of caveat {}",
+                parameter.name, parameter.parameter_type, caveat
+            ),
+            SchemaChange::CaveatParameterRemoved { caveat, parameter } => write!(
+                f,
+                "- parameter {} {} of caveat {}",
+                parameter.name, parameter.parameter_type, caveat
+            ),
+            SchemaChange::CaveatParameterTypeChanged {
+                caveat,
+                parameter,
+                previous_type,
+            } => write!(
+                f,
+                "~ parameter {} of caveat {} from {} to {}",
+                parameter.name, caveat, previous_type, parameter.parameter_type
+            ),
+        }
+    }
+}
+
+impl TryFrom<spicedb::ExpSchemaDiff> for SchemaChange {
+    type Error = Error;
+
+    fn try_from(diff: spicedb::ExpSchemaDiff) -> Result<Self, Self::Error> {
+        fn relation(relation: spicedb::ExpRelation) -> (String, RelationDefinition) {
+            (relation.parent_definition_name.clone(), relation.into())
+        }
+        fn permission(permission: spicedb::ExpPermission) -> (String, PermissionDefinition) {
+            (permission.parent_definition_name.clone(), permission.into())
+        }
+        fn subject_type_change(
+            change: spicedb::ExpRelationSubjectTypeChange,
+        ) -> Option<(String, String, SubjectType)> {
+            let relation = change.relation?;
+            Some((
+                relation.parent_definition_name,
+                relation.name,
+                change.changed_subject_type?.into(),
+            ))
+        }
+        let missing_subject_type = || Error::invalid_response("subject type change expected");
+
+        let diff = diff
+            .diff
+            .ok_or_else(|| Error::invalid_response("schema diff expected"))?;
+        let change = match diff {
+            Diff::DefinitionAdded(d) => SchemaChange::DefinitionAdded(d.into()),
+            Diff::DefinitionRemoved(d) => SchemaChange::DefinitionRemoved(d.into()),
+            Diff::DefinitionDocCommentChanged(d) => {
+                SchemaChange::DefinitionCommentChanged(d.into())
+            }
+            Diff::RelationAdded(r) => {
+                let (definition, relation) = relation(r);
+                SchemaChange::RelationAdded {
+                    definition,
+                    relation,
+                }
+            }
+            Diff::RelationRemoved(r) => {
+                let (definition, relation) = relation(r);
+                SchemaChange::RelationRemoved {
+                    definition,
+                    relation,
+                }
+            }
+            Diff::RelationDocCommentChanged(r) => {
+                let (definition, relation) = relation(r);
+                SchemaChange::RelationCommentChanged {
+                    definition,
+                    relation,
+                }
+            }
+            Diff::RelationSubjectTypeAdded(change) => {
+                let (definition, relation, subject_type) =
+                    subject_type_change(change).ok_or_else(missing_subject_type)?;
+                SchemaChange::SubjectTypeAdded {
+                    definition,
+                    relation,
+                    subject_type,
+                }
+            }
+            Diff::RelationSubjectTypeRemoved(change) => {
+                let (definition, relation, subject_type) =
+                    subject_type_change(change).ok_or_else(missing_subject_type)?;
+                SchemaChange::SubjectTypeRemoved {
+                    definition,
+                    relation,
+                    subject_type,
+                }
+            }
+            Diff::PermissionAdded(p) => {
+                let (definition, permission) = permission(p);
+                SchemaChange::PermissionAdded {
+                    definition,
+                    permission,
+                }
+            }
+            Diff::PermissionRemoved(p) => {
+                let (definition, permission) = permission(p);
+                SchemaChange::PermissionRemoved {
+                    definition,
+                    permission,
+                }
+            }
+            Diff::PermissionDocCommentChanged(p) => {
+                let (definition, permission) = permission(p);
+                SchemaChange::PermissionCommentChanged {
+                    definition,
+                    permission,
+                }
+            }
+            Diff::PermissionExprChanged(p) => {
+                let (definition, permission) = permission(p);
+                SchemaChange::PermissionExpressionChanged {
+                    definition,
+                    permission,
+                }
+            }
+            Diff::CaveatAdded(c) => SchemaChange::CaveatAdded(c.into()),
+            Diff::CaveatRemoved(c) => SchemaChange::CaveatRemoved(c.into()),
+            Diff::CaveatDocCommentChanged(c) => SchemaChange::CaveatCommentChanged(c.into()),
+            Diff::CaveatExprChanged(c) => SchemaChange::CaveatExpressionChanged(c.into()),
+            Diff::CaveatParameterAdded(p) => SchemaChange::CaveatParameterAdded {
+                caveat: p.parent_caveat_name.clone(),
+                parameter: p.into(),
+            },
+            Diff::CaveatParameterRemoved(p) => SchemaChange::CaveatParameterRemoved {
+                caveat: p.parent_caveat_name.clone(),
+                parameter: p.into(),
+            },
+            Diff::CaveatParameterTypeChanged(change) => {
+                let parameter = change
+                    .parameter
+                    .ok_or_else(|| Error::invalid_response("caveat parameter expected"))?;
+                SchemaChange::CaveatParameterTypeChanged {
+                    caveat: parameter.parent_caveat_name.clone(),
+                    parameter: parameter.into(),
+                    previous_type: change.previous_type,
+                }
+            }
+        };
+        Ok(change)
+    }
+}
+
+/// Changes needed to go from the deployed schema to `schema`, see
+/// `SpiceDBClient::plan_schema_change`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaChangePlan {
+    /// Text of the planned schema
+    pub schema: String,
+    pub changes: Vec<SchemaChange>,
+    /// Revision of the deployed schema the plan was computed against
+    pub read_at: Option<spicedb::ZedToken>,
+}
+
+impl SchemaChangePlan {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn destructive_changes(&self) -> impl Iterator<Item = &SchemaChange> {
+        self.changes.iter().filter(|c| c.is_destructive())
+    }
+}
+
+/// Lists the changes one per line, prefixed with `+` for additions, `-` for removals and `~`
+/// for modifications.
+impl fmt::Display for SchemaChangePlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+/// Which destructive changes `apply_schema_change` may apply.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SchemaChangePolicy {
+    /// Refuse every destructive change
+    Strict,
+    /// Allow destructive changes that don't affect any stored relationships, e.g. removing a
+    /// relation without relationships or a permission. Caveat removals and parameter changes
+    /// can't be checked and are refused.
+    #[default]
+    AllowUnused,
+    /// Apply all changes, SpiceDB still rejects schemas that orphan relationships
+    AllowDestructive,
+}
+
+async fn has_relationships(
+    client: &SpiceDBClient,
+    filter: spicedb::RelationshipFilter,
+) -> GrpcResult<bool> {
+    let mut request = client.read_relationships_request();
+    request
+        .with_relationship_filter(filter)
+        .with_consistency(Consistency::FullyConsistent)
+        .with_limit(1);
+    let mut stream = Box::pin(request.send().await?);
+    Ok(stream.next().await.transpose()?.is_some())
+}
+
+pub(crate) async fn check_policy(
+    client: &SpiceDBClient,
+    plan: &SchemaChangePlan,
+    policy: SchemaChangePolicy,
+) -> GrpcResult<()> {
+    for change in plan.destructive_changes() {
+        let allowed = match policy {
+            SchemaChangePolicy::Strict => false,
+            SchemaChangePolicy::AllowDestructive => true,
+            SchemaChangePolicy::AllowUnused => match change {
+                SchemaChange::PermissionRemoved { .. } => true,
+                _ => match change.affected_relationships() {
+                    Some(filter) => !has_relationships(client, filter).await?,
+                    None => false,
+                },
+            },
+        };
+        if !allowed {
+            return Err(Error::SchemaChangeRefused(Box::new(change.clone())));
+        }
+    }
+    Ok(())
+}
@@ -1,5 +1,24 @@
+pub mod ast;
+mod builder;
+pub(crate) mod change;
+mod parser;
+mod reflection;
+mod verification;
+
 use crate::grpc::AuthenticatedChannel;
 use crate::spicedb;
 
 pub type SpiceDBSchemaClient =
     spicedb::schema_service_client::SchemaServiceClient<AuthenticatedChannel>;
+
+pub use builder::{
+    arrow, nil, perm, rel, subject, subject_relation, wildcard, DefinitionBuilder, PermissionExpr,
+    SchemaBuildError, SchemaBuilder,
+};
+pub use change::{SchemaChange, SchemaChangePlan, SchemaChangePolicy};
+pub use parser::{parse_caveat_type, parse_expression, parse_schema, ParseError};
+pub use reflection::{
+    CaveatDefinition, CaveatParameter, Definition, PermissionDefinition, RelationDefinition,
+    Schema, SubjectType, SubjectTypeKind,
+};
+pub use verification::{SchemaDriftReport, SchemaMismatch, SchemaRegistry};
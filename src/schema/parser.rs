@@ -0,0 +1,515 @@
+use std::fmt;
+
+use super::ast::{
+    Caveat, CaveatParameter, CaveatType, Definition, Expr, ExprKind, Item, Member, Permission,
+    Relation, Schema, Span, SubjectType, SubjectTypeKind, UseDirective,
+};
+
+/// Error parsing schema text, pointing at the offending token.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub message: String,
+    pub span: Span,
+    /// 1-based line of `span.start`
+    pub line: usize,
+    /// 1-based column in characters of `span.start`
+    pub column: usize,
+}
+
+impl ParseError {
+    fn new(text: &str, span: Span, message: impl Into<String>) -> Self {
+        let before = &text[..span.start];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        ParseError {
+            message: message.into(),
+            span,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.message, self.line, self.column
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses the text of a schema, as accepted by `write_schema`, into its syntax tree.
+///
+/// Comments directly preceding a definition, caveat, relation or permission are kept on that
+/// node, all other comments are dropped.
+pub fn parse_schema(text: &str) -> Result<Schema, ParseError> {
+    let mut parser = Parser {
+        text,
+        tokens: tokenize(text)?,
+        pos: 0,
+    };
+    let mut items = vec![];
+    loop {
+        let comments = parser.comments();
+        if parser.peek().is_none() {
+            break;
+        }
+        let (keyword, span) = parser.ident()?;
+        let item = match keyword.as_str() {
+            "definition" => Item::Definition(parser.definition(span, comments)?),
+            "caveat" => Item::Caveat(parser.caveat(span, comments)?),
+            "use" => {
+                let (feature, end) = parser.ident()?;
+                Item::Use(UseDirective {
+                    feature,
+                    span: span.to(end),
+                })
+            }
+            _ => {
+                return Err(parser.error(
+                    span,
+                    format!(
+                        "Expected `definition`, `caveat` or `use`, found `{}`",
+                        keyword
+                    ),
+                ))
+            }
+        };
+        items.push(item);
+    }
+    Ok(Schema { items })
+}
+
+/// Parses a permission expression on its own, e.g. `reader + parent->view`.
+pub fn parse_expression(text: &str) -> Result<Expr, ParseError> {
+    parse_all(text, |parser| parser.exclusion())
+}
+
+/// Parses a caveat parameter type on its own, e.g. `list<string>`.
+pub fn parse_caveat_type(text: &str) -> Result<CaveatType, ParseError> {
+    parse_all(text, |parser| parser.caveat_type().map(|(ty, _)| ty))
+}
+
+fn parse_all<T>(
+    text: &str,
+    parse: impl FnOnce(&mut Parser<'_>) -> Result<T, ParseError>,
+) -> Result<T, ParseError> {
+    let mut parser = Parser {
+        text,
+        tokens: tokenize(text)?,
+        pos: 0,
+    };
+    let parsed = parse(&mut parser)?;
+    match parser.peek() {
+        Some(token) => Err(parser.unexpected(token, "end of input")),
+        None => Ok(parsed),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum TokenKind {
+    Ident(String),
+    Comment,
+    /// String literal, only valid inside caveat expressions
+    Str,
+    Arrow,
+    Punct(char),
+}
+
+#[derive(Clone, Debug)]
+struct Token {
+    kind: TokenKind,
+    span: Span,
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = vec![];
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let next = chars.peek().map(|(_, n)| *n);
+        let kind = if c.is_whitespace() {
+            continue;
+        } else if c == '/' && next == Some('/') {
+            while chars.next_if(|(_, c)| *c != '\n').is_some() {}
+            TokenKind::Comment
+        } else if c == '/' && next == Some('*') {
+            chars.next();
+            let mut last = ' ';
+            loop {
+                match chars.next() {
+                    Some((_, '/')) if last == '*' => break,
+                    Some((_, c)) => last = c,
+                    None => {
+                        return Err(ParseError::new(
+                            text,
+                            Span::new(start, text.len()),
+                            "Unterminated block comment",
+                        ))
+                    }
+                }
+            }
+            TokenKind::Comment
+        } else if c == '-' && next == Some('>') {
+            chars.next();
+            TokenKind::Arrow
+        } else if is_ident_char(c) {
+            // Definition names can be prefixed by namespaces, `org/team`
+            loop {
+                let mut lookahead = chars.clone();
+                match lookahead.next() {
+                    Some((_, c)) if is_ident_char(c) => {}
+                    Some((_, '/')) if lookahead.next().is_some_and(|(_, c)| is_ident_char(c)) => {}
+                    _ => break,
+                }
+                chars.next();
+            }
+            let end = chars.peek().map(|(i, _)| *i).unwrap_or(text.len());
+            TokenKind::Ident(text[start..end].to_owned())
+        } else if c == '"' || c == '\'' {
+            let mut escaped = false;
+            loop {
+                match chars.next() {
+                    Some((_, '\\')) if !escaped => escaped = true,
+                    Some((_, q)) if q == c && !escaped => break,
+                    Some(_) => escaped = false,
+                    None => {
+                        return Err(ParseError::new(
+                            text,
+                            Span::new(start, text.len()),
+                            "Unterminated string literal",
+                        ))
+                    }
+                }
+            }
+            TokenKind::Str
+        } else {
+            TokenKind::Punct(c)
+        };
+        let end = chars.peek().map(|(i, _)| *i).unwrap_or(text.len());
+        tokens.push(Token {
+            kind,
+            span: Span::new(start, end),
+        });
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, span: Span, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.text, span, message)
+    }
+
+    /// Consumes the comments before the next token.
+    fn comments(&mut self) -> Vec<String> {
+        let mut comments = vec![];
+        while let Some(token) = self.tokens.get(self.pos) {
+            if token.kind != TokenKind::Comment {
+                break;
+            }
+            comments.push(self.text[token.span.start..token.span.end].to_owned());
+            self.pos += 1;
+        }
+        comments
+    }
+
+    /// Index of the next token that isn't a comment
+    fn lookahead(&self) -> usize {
+        self.pos
+            + self.tokens[self.pos..]
+                .iter()
+                .take_while(|token| token.kind == TokenKind::Comment)
+                .count()
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.lookahead())
+    }
+
+    fn next(&mut self) -> Result<Token, ParseError> {
+        let pos = self.lookahead();
+        match self.tokens.get(pos).cloned() {
+            Some(token) => {
+                self.pos = pos + 1;
+                Ok(token)
+            }
+            None => {
+                let end = Span::new(self.text.len(), self.text.len());
+                Err(self.error(end, "Unexpected end of schema"))
+            }
+        }
+    }
+
+    fn unexpected(&self, token: &Token, expected: &str) -> ParseError {
+        let found = &self.text[token.span.start..token.span.end];
+        self.error(
+            token.span,
+            format!("Expected {}, found `{}`", expected, found),
+        )
+    }
+
+    fn ident(&mut self) -> Result<(String, Span), ParseError> {
+        let token = self.next()?;
+        match token.kind {
+            TokenKind::Ident(ident) => Ok((ident, token.span)),
+            _ => Err(self.unexpected(&token, "identifier")),
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<Span, ParseError> {
+        let token = self.next()?;
+        match &token.kind {
+            TokenKind::Ident(ident) if ident == keyword => Ok(token.span),
+            _ => Err(self.unexpected(&token, &format!("`{}`", keyword))),
+        }
+    }
+
+    fn expect(&mut self, punct: char) -> Result<Span, ParseError> {
+        let token = self.next()?;
+        match token.kind {
+            TokenKind::Punct(c) if c == punct => Ok(token.span),
+            _ => Err(self.unexpected(&token, &format!("`{}`", punct))),
+        }
+    }
+
+    fn eat(&mut self, kind: TokenKind) -> Option<Span> {
+        let pos = self.lookahead();
+        match self.tokens.get(pos) {
+            Some(token) if token.kind == kind => {
+                self.pos = pos + 1;
+                Some(token.span)
+            }
+            _ => None,
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> Option<Span> {
+        self.eat(TokenKind::Ident(keyword.to_owned()))
+    }
+
+    fn definition(&mut self, start: Span, comments: Vec<String>) -> Result<Definition, ParseError> {
+        let (name, _) = self.ident()?;
+        self.expect('{')?;
+        let mut members = vec![];
+        let end = loop {
+            let comments = self.comments();
+            if let Some(end) = self.eat(TokenKind::Punct('}')) {
+                break end;
+            }
+            let (keyword, span) = self.ident()?;
+            let member = match keyword.as_str() {
+                "relation" => Member::Relation(self.relation(span, comments)?),
+                "permission" => Member::Permission(self.permission(span, comments)?),
+                _ => {
+                    return Err(self.error(
+                        span,
+                        format!("Expected `relation` or `permission`, found `{}`", keyword),
+                    ))
+                }
+            };
+            members.push(member);
+        };
+        Ok(Definition {
+            name,
+            comments,
+            members,
+            span: start.to(end),
+        })
+    }
+
+    fn relation(&mut self, start: Span, comments: Vec<String>) -> Result<Relation, ParseError> {
+        let (name, _) = self.ident()?;
+        self.expect(':')?;
+        let mut subject_types = vec![self.subject_type()?];
+        while self.eat(TokenKind::Punct('|')).is_some() {
+            subject_types.push(self.subject_type()?);
+        }
+        let end = subject_types.last().map(|s| s.span).unwrap_or(start);
+        Ok(Relation {
+            name,
+            comments,
+            subject_types,
+            span: start.to(end),
+        })
+    }
+
+    fn subject_type(&mut self) -> Result<SubjectType, ParseError> {
+        let (definition, mut span) = self.ident()?;
+        let kind = if self.eat(TokenKind::Punct(':')).is_some() {
+            span = span.to(self.expect('*')?);
+            SubjectTypeKind::Wildcard
+        } else if self.eat(TokenKind::Punct('#')).is_some() {
+            let (relation, end) = self.ident()?;
+            span = span.to(end);
+            SubjectTypeKind::Relation(relation)
+        } else {
+            SubjectTypeKind::Terminal
+        };
+        let mut caveat = None;
+        let mut expiration = false;
+        if self.eat_keyword("with").is_some() {
+            let (name, end) = self.ident()?;
+            span = span.to(end);
+            if name == "expiration" {
+                expiration = true;
+            } else {
+                caveat = Some(name);
+                if self.eat_keyword("and").is_some() {
+                    span = span.to(self.keyword("expiration")?);
+                    expiration = true;
+                }
+            }
+        }
+        Ok(SubjectType {
+            definition,
+            kind,
+            caveat,
+            expiration,
+            span,
+        })
+    }
+
+    fn permission(&mut self, start: Span, comments: Vec<String>) -> Result<Permission, ParseError> {
+        let (name, _) = self.ident()?;
+        self.expect('=')?;
+        let expression = self.exclusion()?;
+        Ok(Permission {
+            name,
+            comments,
+            span: start.to(expression.span),
+            expression,
+        })
+    }
+
+    /// Parses a left associative chain of `operand (op operand)*`.
+    fn binary(
+        &mut self,
+        op: char,
+        operand: fn(&mut Self) -> Result<Expr, ParseError>,
+        node: fn(Box<Expr>, Box<Expr>) -> ExprKind,
+    ) -> Result<Expr, ParseError> {
+        let mut left = operand(self)?;
+        while self.eat(TokenKind::Punct(op)).is_some() {
+            let right = operand(self)?;
+            let span = left.span.to(right.span);
+            left = Expr {
+                kind: node(Box::new(left), Box::new(right)),
+                span,
+            };
+        }
+        Ok(left)
+    }
+
+    fn exclusion(&mut self) -> Result<Expr, ParseError> {
+        self.binary('-', Self::intersection, ExprKind::Exclusion)
+    }
+
+    fn intersection(&mut self) -> Result<Expr, ParseError> {
+        self.binary('&', Self::union, ExprKind::Intersection)
+    }
+
+    fn union(&mut self) -> Result<Expr, ParseError> {
+        self.binary('+', Self::operand, ExprKind::Union)
+    }
+
+    fn operand(&mut self) -> Result<Expr, ParseError> {
+        let token = self.next()?;
+        match token.kind {
+            TokenKind::Punct('(') => {
+                let inner = self.exclusion()?;
+                let end = self.expect(')')?;
+                Ok(Expr {
+                    kind: inner.kind,
+                    span: token.span.to(end),
+                })
+            }
+            TokenKind::Ident(ident) if ident == "nil" => Ok(Expr {
+                kind: ExprKind::Nil,
+                span: token.span,
+            }),
+            TokenKind::Ident(relation) => {
+                if self.eat(TokenKind::Arrow).is_some() {
+                    let (permission, end) = self.ident()?;
+                    Ok(Expr {
+                        kind: ExprKind::Arrow {
+                            relation,
+                            permission,
+                        },
+                        span: token.span.to(end),
+                    })
+                } else {
+                    Ok(Expr {
+                        kind: ExprKind::Reference(relation),
+                        span: token.span,
+                    })
+                }
+            }
+            _ => Err(self.unexpected(&token, "relation, permission, `nil` or `(`")),
+        }
+    }
+
+    fn caveat(&mut self, start: Span, comments: Vec<String>) -> Result<Caveat, ParseError> {
+        let (name, _) = self.ident()?;
+        self.expect('(')?;
+        let mut parameters = vec![];
+        loop {
+            let (parameter, span) = self.ident()?;
+            let (parameter_type, end) = self.caveat_type()?;
+            parameters.push(CaveatParameter {
+                name: parameter,
+                parameter_type,
+                span: span.to(end),
+            });
+            if self.eat(TokenKind::Punct(',')).is_none() {
+                break;
+            }
+        }
+        self.expect(')')?;
+        let open = self.expect('{')?;
+        let mut depth = 1;
+        let close = loop {
+            let token = self.next()?;
+            match token.kind {
+                TokenKind::Punct('{') => depth += 1,
+                TokenKind::Punct('}') if depth == 1 => break token.span,
+                TokenKind::Punct('}') => depth -= 1,
+                _ => {}
+            }
+        };
+        Ok(Caveat {
+            name,
+            comments,
+            parameters,
+            expression: self.text[open.end..close.start].trim().to_owned(),
+            span: start.to(close),
+        })
+    }
+
+    fn caveat_type(&mut self) -> Result<(CaveatType, Span), ParseError> {
+        let (name, mut span) = self.ident()?;
+        let mut type_args = vec![];
+        if self.eat(TokenKind::Punct('<')).is_some() {
+            loop {
+                let (arg, _) = self.caveat_type()?;
+                type_args.push(arg);
+                if self.eat(TokenKind::Punct(',')).is_none() {
+                    break;
+                }
+            }
+            span = span.to(self.expect('>')?);
+        }
+        Ok((CaveatType { name, type_args }, span))
+    }
+}
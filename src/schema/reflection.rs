@@ -0,0 +1,281 @@
+use std::fmt;
+
+use super::ast;
+use crate::spicedb;
+use crate::spicedb::exp_type_reference::Typeref;
+
+/// Owned model of a deployed schema as returned by `ExperimentalReflectSchema`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Schema {
+    pub definitions: Vec<Definition>,
+    pub caveats: Vec<CaveatDefinition>,
+    pub read_at: Option<spicedb::ZedToken>,
+}
+
+impl Schema {
+    pub fn definition(&self, name: &str) -> Option<&Definition> {
+        self.definitions.iter().find(|d| d.name == name)
+    }
+
+    pub fn caveat(&self, name: &str) -> Option<&CaveatDefinition> {
+        self.caveats.iter().find(|c| c.name == name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Definition {
+    pub name: String,
+    /// Doc comment including its delimiter characters, empty if there is none
+    pub comment: String,
+    pub relations: Vec<RelationDefinition>,
+    pub permissions: Vec<PermissionDefinition>,
+}
+
+impl Definition {
+    pub fn relation(&self, name: &str) -> Option<&RelationDefinition> {
+        self.relations.iter().find(|r| r.name == name)
+    }
+
+    pub fn permission(&self, name: &str) -> Option<&PermissionDefinition> {
+        self.permissions.iter().find(|p| p.name == name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RelationDefinition {
+    pub name: String,
+    pub comment: String,
+    pub subject_types: Vec<SubjectType>,
+}
+
+impl RelationDefinition {
+    pub fn allowed_subject_types(&self) -> &[SubjectType] {
+        &self.subject_types
+    }
+}
+
+/// One of the allowed subject types of a relation, e.g. `user`, `group#member` or `user:*`,
+/// optionally `with` a caveat.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubjectType {
+    pub definition: String,
+    pub kind: SubjectTypeKind,
+    pub caveat: Option<String>,
+}
+
+impl fmt::Display for SubjectType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.definition)?;
+        match &self.kind {
+            SubjectTypeKind::Terminal => {}
+            SubjectTypeKind::Relation(relation) => write!(f, "#{}", relation)?,
+            SubjectTypeKind::Wildcard => write!(f, ":*")?,
+        }
+        if let Some(caveat) = &self.caveat {
+            write!(f, " with {}", caveat)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SubjectTypeKind {
+    /// The subject is referenced directly, `user`
+    Terminal,
+    /// The subject is referenced through one of its relations, `group#member`
+    Relation(String),
+    /// Any subject of the definition, `user:*`
+    Wildcard,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PermissionDefinition {
+    pub name: String,
+    pub comment: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CaveatDefinition {
+    pub name: String,
+    pub comment: String,
+    pub parameters: Vec<CaveatParameter>,
+    pub expression: String,
+}
+
+impl CaveatDefinition {
+    pub fn parameter(&self, name: &str) -> Option<&CaveatParameter> {
+        self.parameters.iter().find(|p| p.name == name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CaveatParameter {
+    pub name: String,
+    /// Type as written in the schema, e.g. `int` or `list<string>`
+    pub parameter_type: String,
+}
+
+impl From<spicedb::ExperimentalReflectSchemaResponse> for Schema {
+    fn from(resp: spicedb::ExperimentalReflectSchemaResponse) -> Self {
+        Schema {
+            definitions: resp.definitions.into_iter().map(Into::into).collect(),
+            caveats: resp.caveats.into_iter().map(Into::into).collect(),
+            read_at: resp.read_at,
+        }
+    }
+}
+
+impl From<spicedb::ExpDefinition> for Definition {
+    fn from(definition: spicedb::ExpDefinition) -> Self {
+        Definition {
+            name: definition.name,
+            comment: definition.comment,
+            relations: definition.relations.into_iter().map(Into::into).collect(),
+            permissions: definition.permissions.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<spicedb::ExpRelation> for RelationDefinition {
+    fn from(relation: spicedb::ExpRelation) -> Self {
+        RelationDefinition {
+            name: relation.name,
+            comment: relation.comment,
+            subject_types: relation.subject_types.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<spicedb::ExpTypeReference> for SubjectType {
+    fn from(type_reference: spicedb::ExpTypeReference) -> Self {
+        let kind = match type_reference.typeref {
+            Some(Typeref::OptionalRelationName(relation)) => SubjectTypeKind::Relation(relation),
+            Some(Typeref::IsPublicWildcard(true)) => SubjectTypeKind::Wildcard,
+            _ => SubjectTypeKind::Terminal,
+        };
+        SubjectType {
+            definition: type_reference.subject_definition_name,
+            kind,
+            caveat: Some(type_reference.optional_caveat_name).filter(|c| !c.is_empty()),
+        }
+    }
+}
+
+impl From<spicedb::ExpPermission> for PermissionDefinition {
+    fn from(permission: spicedb::ExpPermission) -> Self {
+        PermissionDefinition {
+            name: permission.name,
+            comment: permission.comment,
+        }
+    }
+}
+
+impl From<spicedb::ExpCaveat> for CaveatDefinition {
+    fn from(caveat: spicedb::ExpCaveat) -> Self {
+        CaveatDefinition {
+            name: caveat.name,
+            comment: caveat.comment,
+            parameters: caveat.parameters.into_iter().map(Into::into).collect(),
+            expression: caveat.expression,
+        }
+    }
+}
+
+impl From<spicedb::ExpCaveatParameter> for CaveatParameter {
+    fn from(parameter: spicedb::ExpCaveatParameter) -> Self {
+        CaveatParameter {
+            name: parameter.name,
+            parameter_type: parameter.r#type,
+        }
+    }
+}
+
+impl From<ast::Schema> for Schema {
+    fn from(schema: ast::Schema) -> Self {
+        let mut definitions = vec![];
+        let mut caveats = vec![];
+        for item in schema.items {
+            match item {
+                ast::Item::Definition(definition) => definitions.push(definition.into()),
+                ast::Item::Caveat(caveat) => caveats.push(caveat.into()),
+                ast::Item::Use(_) => {}
+            }
+        }
+        Schema {
+            definitions,
+            caveats,
+            read_at: None,
+        }
+    }
+}
+
+impl From<ast::Definition> for Definition {
+    fn from(definition: ast::Definition) -> Self {
+        let mut relations = vec![];
+        let mut permissions = vec![];
+        for member in definition.members {
+            match member {
+                ast::Member::Relation(relation) => relations.push(relation.into()),
+                ast::Member::Permission(permission) => permissions.push(permission.into()),
+            }
+        }
+        Definition {
+            name: definition.name,
+            comment: definition.comments.join("\n"),
+            relations,
+            permissions,
+        }
+    }
+}
+
+impl From<ast::Relation> for RelationDefinition {
+    fn from(relation: ast::Relation) -> Self {
+        RelationDefinition {
+            name: relation.name,
+            comment: relation.comments.join("\n"),
+            subject_types: relation.subject_types.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<ast::SubjectType> for SubjectType {
+    fn from(subject_type: ast::SubjectType) -> Self {
+        let kind = match subject_type.kind {
+            ast::SubjectTypeKind::Terminal => SubjectTypeKind::Terminal,
+            ast::SubjectTypeKind::Relation(relation) => SubjectTypeKind::Relation(relation),
+            ast::SubjectTypeKind::Wildcard => SubjectTypeKind::Wildcard,
+        };
+        SubjectType {
+            definition: subject_type.definition,
+            kind,
+            caveat: subject_type.caveat,
+        }
+    }
+}
+
+impl From<ast::Permission> for PermissionDefinition {
+    fn from(permission: ast::Permission) -> Self {
+        PermissionDefinition {
+            name: permission.name,
+            comment: permission.comments.join("\n"),
+        }
+    }
+}
+
+impl From<ast::Caveat> for CaveatDefinition {
+    fn from(caveat: ast::Caveat) -> Self {
+        CaveatDefinition {
+            name: caveat.name,
+            comment: caveat.comments.join("\n"),
+            parameters: caveat
+                .parameters
+                .into_iter()
+                .map(|parameter| CaveatParameter {
+                    name: parameter.name,
+                    parameter_type: parameter.parameter_type.to_string(),
+                })
+                .collect(),
+            expression: caveat.expression,
+        }
+    }
+}
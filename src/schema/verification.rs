@@ -0,0 +1,181 @@
+use std::fmt;
+
+use strum::IntoEnumIterator;
+
+use crate::schema::Schema;
+use crate::{Caveat, Entity, Permission, Relation, Resource};
+
+#[derive(Clone, Debug, PartialEq)]
+struct RegisteredEntity {
+    object_type: &'static str,
+    relations: Vec<&'static str>,
+    permissions: Vec<&'static str>,
+}
+
+/// Collects the `Entity`, `Resource` and `Caveat` types a service relies on, so they can be
+/// verified against the deployed schema with `SpiceDBClient::verify_schema`.
+///
+/// Enumerating the `Relations` and `Permissions` requires them to implement
+/// `strum::IntoEnumIterator`, which can be derived with `EnumIter`.
+#[derive(Clone, Debug, Default)]
+pub struct SchemaRegistry {
+    entities: Vec<RegisteredEntity>,
+    caveats: Vec<&'static str>,
+}
+
+impl SchemaRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register_entity<E>(&mut self) -> &mut Self
+    where
+        E: Entity,
+        E::Relations: IntoEnumIterator,
+    {
+        self.entities.push(RegisteredEntity {
+            object_type: E::object_type(),
+            relations: E::Relations::iter().map(Relation::name).collect(),
+            permissions: vec![],
+        });
+        self
+    }
+
+    pub fn register_resource<R>(&mut self) -> &mut Self
+    where
+        R: Resource,
+        R::Relations: IntoEnumIterator,
+        R::Permissions: IntoEnumIterator,
+    {
+        self.entities.push(RegisteredEntity {
+            object_type: R::object_type(),
+            relations: R::Relations::iter().map(Relation::name).collect(),
+            permissions: R::Permissions::iter().map(Permission::name).collect(),
+        });
+        self
+    }
+
+    pub fn register_caveat<C>(&mut self) -> &mut Self
+    where
+        C: Caveat,
+    {
+        self.caveats.push(C::name());
+        self
+    }
+
+    /// Compares the registered types against `schema`. A registered permission is also satisfied
+    /// by a relation of the same name, since SpiceDB can check both.
+    pub fn verify(&self, schema: &Schema) -> SchemaDriftReport {
+        let mut mismatches = vec![];
+        for entity in &self.entities {
+            let Some(definition) = schema.definition(entity.object_type) else {
+                mismatches.push(SchemaMismatch::MissingDefinition {
+                    definition: entity.object_type.to_owned(),
+                });
+                continue;
+            };
+            for relation in &entity.relations {
+                if definition.relation(relation).is_none() {
+                    mismatches.push(SchemaMismatch::MissingRelation {
+                        definition: entity.object_type.to_owned(),
+                        relation: (*relation).to_owned(),
+                    });
+                }
+            }
+            for permission in &entity.permissions {
+                if definition.permission(permission).is_none()
+                    && definition.relation(permission).is_none()
+                {
+                    mismatches.push(SchemaMismatch::MissingPermission {
+                        definition: entity.object_type.to_owned(),
+                        permission: (*permission).to_owned(),
+                    });
+                }
+            }
+        }
+        for caveat in &self.caveats {
+            if schema.caveat(caveat).is_none() {
+                mismatches.push(SchemaMismatch::MissingCaveat {
+                    caveat: (*caveat).to_owned(),
+                });
+            }
+        }
+        mismatches.dedup();
+        SchemaDriftReport { mismatches }
+    }
+}
+
+/// Something a registered type relies on that does not exist in the deployed schema
+#[derive(Clone, Debug, PartialEq)]
+pub enum SchemaMismatch {
+    MissingDefinition {
+        definition: String,
+    },
+    MissingRelation {
+        definition: String,
+        relation: String,
+    },
+    MissingPermission {
+        definition: String,
+        permission: String,
+    },
+    MissingCaveat {
+        caveat: String,
+    },
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaMismatch::MissingDefinition { definition } => {
+                write!(f, "definition `{}` is missing", definition)
+            }
+            SchemaMismatch::MissingRelation {
+                definition,
+                relation,
+            } => write!(f, "relation `{}#{}` is missing", definition, relation),
+            SchemaMismatch::MissingPermission {
+                definition,
+                permission,
+            } => write!(f, "permission `{}#{}` is missing", definition, permission),
+            SchemaMismatch::MissingCaveat { caveat } => {
+                write!(f, "caveat `{}` is missing", caveat)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaDriftReport {
+    pub mismatches: Vec<SchemaMismatch>,
+}
+
+impl SchemaDriftReport {
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// Turns a report with mismatches into an error, to fail startup with `?`.
+    pub fn into_result(self) -> Result<(), SchemaDriftReport> {
+        if self.is_ok() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Display for SchemaDriftReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "schema drift detected: ")?;
+        for (i, mismatch) in self.mismatches.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", mismatch)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SchemaDriftReport {}
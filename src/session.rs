@@ -0,0 +1,151 @@
+//! Read-your-writes consistency without passing `ZedToken`s around.
+//!
+//! A client scoped to a session key with `SpiceDBClient::session` records the `ZedToken` of every
+//! write in its `ZedTokenStore`, and reads without an explicit consistency use
+//! `Consistency::AtLeastAsFresh` of the stored token for the same key:
+//!
+//! ```ignore
+//! let session = client.session(format!("user:{}", user_id));
+//! session.create_relationships(updates, []).await?;
+//! // sees the relationships written above, even on a different replica of the service
+//! session.check_permission::<Document>(&actor, "readme", DocumentPermission::Read).await?;
+//! ```
+//!
+//! The key can be anything identifying the data a caller needs fresh, e.g. a user, tenant or
+//! resource. Reads of clients without a session key are unaffected.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::grpc::GrpcResult;
+use crate::spicedb;
+use crate::spicedb::wrappers::Consistency;
+use crate::Error;
+
+/// Stores the latest `ZedToken` written per session key. Implement it on a shared cache like
+/// Redis to get read-your-writes across several instances of a service.
+#[tonic::async_trait]
+pub trait ZedTokenStore: Send + Sync {
+    async fn get(&self, key: &str) -> anyhow::Result<Option<spicedb::ZedToken>>;
+    async fn put(&self, key: &str, token: &spicedb::ZedToken) -> anyhow::Result<()>;
+}
+
+#[tonic::async_trait]
+impl<T> ZedTokenStore for Arc<T>
+where
+    T: ZedTokenStore + ?Sized,
+{
+    async fn get(&self, key: &str) -> anyhow::Result<Option<spicedb::ZedToken>> {
+        (**self).get(key).await
+    }
+
+    async fn put(&self, key: &str, token: &spicedb::ZedToken) -> anyhow::Result<()> {
+        (**self).put(key, token).await
+    }
+}
+
+/// Keeps one token per key in memory, the store of clients that weren't given one.
+#[derive(Debug, Default)]
+pub struct InMemoryZedTokenStore {
+    tokens: Mutex<HashMap<String, spicedb::ZedToken>>,
+}
+
+impl InMemoryZedTokenStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn remove(&self, key: &str) -> Option<spicedb::ZedToken> {
+        self.tokens.lock().unwrap().remove(key)
+    }
+}
+
+#[tonic::async_trait]
+impl ZedTokenStore for InMemoryZedTokenStore {
+    async fn get(&self, key: &str) -> anyhow::Result<Option<spicedb::ZedToken>> {
+        Ok(self.tokens.lock().unwrap().get(key).cloned())
+    }
+
+    async fn put(&self, key: &str, token: &spicedb::ZedToken) -> anyhow::Result<()> {
+        self.tokens
+            .lock()
+            .unwrap()
+            .insert(key.to_owned(), token.clone());
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct TokenStore(Arc<dyn ZedTokenStore>);
+
+impl TokenStore {
+    pub(crate) fn new(store: impl ZedTokenStore + 'static) -> Self {
+        TokenStore(Arc::new(store))
+    }
+}
+
+impl Default for TokenStore {
+    fn default() -> Self {
+        Self::new(InMemoryZedTokenStore::new())
+    }
+}
+
+impl fmt::Debug for TokenStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TokenStore")
+    }
+}
+
+/// Session key and store handed to the requests of a session scoped client
+#[derive(Clone, Debug)]
+pub(crate) struct Session {
+    store: TokenStore,
+    key: String,
+}
+
+impl Session {
+    pub(crate) fn new(store: TokenStore, key: String) -> Self {
+        Session { store, key }
+    }
+
+    /// Sets `consistency` to at least as fresh as the last write of the session, unless the
+    /// request already has one.
+    pub(crate) async fn apply(
+        session: Option<&Session>,
+        consistency: &mut Option<spicedb::Consistency>,
+    ) -> GrpcResult<()> {
+        let Some(session) = session else {
+            return Ok(());
+        };
+        if consistency.is_some() {
+            return Ok(());
+        }
+        let token = session
+            .store
+            .0
+            .get(&session.key)
+            .await
+            .map_err(Error::ZedTokenStore)?;
+        if let Some(token) = token {
+            *consistency = Some(Consistency::AtLeastAsFresh(token).into());
+        }
+        Ok(())
+    }
+
+    /// Records the token of a write, a failure is returned even though the write succeeded.
+    pub(crate) async fn record(
+        session: Option<&Session>,
+        token: &spicedb::ZedToken,
+    ) -> GrpcResult<()> {
+        if let Some(session) = session {
+            session
+                .store
+                .0
+                .put(&session.key, token)
+                .await
+                .map_err(Error::ZedTokenStore)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use futures::future::{BoxFuture, Shared};
+use futures::FutureExt;
+
+use crate::grpc::GrpcResult;
+use crate::spicedb;
+use crate::Error;
+
+type Flight<T> = Shared<BoxFuture<'static, Result<T, Arc<Error>>>>;
+
+/// Shares one in-flight call and its result between all callers making the same request, keyed
+/// on the encoded request message. Calls made after the shared one completed are sent again.
+pub(crate) struct SingleFlight<T> {
+    flights: Mutex<HashMap<Vec<u8>, Flight<T>>>,
+}
+
+impl<T> SingleFlight<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    /// Awaits the call in flight for `key`, or starts `call` if there is none. `call` isn't
+    /// polled at all when it joins another call.
+    pub(crate) async fn run(
+        &self,
+        key: Vec<u8>,
+        call: impl Future<Output = GrpcResult<T>> + Send + 'static,
+    ) -> GrpcResult<T> {
+        let flight = self
+            .flights
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_insert_with(|| call.map(|r| r.map_err(Arc::new)).boxed().shared())
+            .clone();
+        let result = flight.clone().await;
+        let mut flights = self.flights.lock().unwrap();
+        if flights.get(&key).is_some_and(|f| f.ptr_eq(&flight)) {
+            flights.remove(&key);
+        }
+        result.map_err(|err| err.duplicate())
+    }
+}
+
+impl<T> Default for SingleFlight<T> {
+    fn default() -> Self {
+        SingleFlight {
+            flights: Mutex::default(),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for SingleFlight<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SingleFlight")
+            .field("in_flight", &self.flights.lock().unwrap().len())
+            .finish()
+    }
+}
+
+/// Calls in flight of a client deduplicating identical requests, shared by its clones.
+#[derive(Debug, Default)]
+pub(crate) struct SingleFlights {
+    pub(crate) check_permission: SingleFlight<spicedb::CheckPermissionResponse>,
+    /// Raw subject ids of `LookupSubjectsRequest::send_collect_ids`
+    pub(crate) lookup_subject_ids: SingleFlight<Vec<String>>,
+}
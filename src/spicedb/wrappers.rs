@@ -1,5 +1,10 @@
+use super::algebraic_subject_set::Operation as AlgebraicOperation;
+use super::check_bulk_permissions_pair::Response as CheckBulkPermissionsPairResponse;
+use super::check_permission_response::Permissionship as CheckPermissionship;
 use super::consistency::Requirement;
+use super::permission_relationship_tree::TreeType;
 use super::LookupPermissionship;
+use crate::Error;
 
 /// Wrapper enum to shorten the expressions needed to construct the gRPC `Consistency` type
 #[derive(Clone, Debug, PartialEq)]
@@ -30,15 +35,28 @@ pub struct SubjectReference {
     pub optional_relation: Option<String>,
 }
 
-impl From<super::SubjectReference> for SubjectReference {
-    fn from(subject: super::SubjectReference) -> Self {
-        SubjectReference {
-            object: subject.object.unwrap(),
+impl TryFrom<super::SubjectReference> for SubjectReference {
+    type Error = Error;
+
+    fn try_from(subject: super::SubjectReference) -> Result<Self, Self::Error> {
+        Ok(SubjectReference {
+            object: subject
+                .object
+                .ok_or_else(|| Error::invalid_response("SubjectReference without an object"))?,
             optional_relation: if subject.optional_relation.is_empty() {
                 None
             } else {
                 Some(subject.optional_relation)
             },
+        })
+    }
+}
+
+impl From<SubjectReference> for super::SubjectReference {
+    fn from(subject: SubjectReference) -> Self {
+        super::SubjectReference {
+            object: Some(subject.object),
+            optional_relation: subject.optional_relation.unwrap_or_default(),
         }
     }
 }
@@ -51,19 +69,38 @@ pub struct Relationship {
     pub optional_caveat: Option<super::ContextualizedCaveat>,
 }
 
-impl From<super::Relationship> for Relationship {
-    fn from(rel: super::Relationship) -> Self {
-        Relationship {
-            resource: rel.resource.unwrap(),
+impl TryFrom<super::Relationship> for Relationship {
+    type Error = Error;
+
+    fn try_from(rel: super::Relationship) -> Result<Self, Self::Error> {
+        let resource = rel
+            .resource
+            .ok_or_else(|| Error::invalid_response("Relationship without a resource"))?;
+        let subject = rel
+            .subject
+            .ok_or_else(|| Error::invalid_response("Relationship without a subject"))?;
+        Ok(Relationship {
+            resource,
             relation: rel.relation,
-            subject: rel.subject.unwrap().into(),
+            subject: subject.try_into()?,
+            optional_caveat: rel.optional_caveat,
+        })
+    }
+}
+
+impl From<Relationship> for super::Relationship {
+    fn from(rel: Relationship) -> Self {
+        super::Relationship {
+            resource: Some(rel.resource),
+            relation: rel.relation,
+            subject: Some(rel.subject.into()),
             optional_caveat: rel.optional_caveat,
         }
     }
 }
 
-/// Response struct without the stupid optional types due to proto3 and a `From` impl that
-/// assumes the `validate` rules defined in the proto file to be upheld, otherwise it panics.
+/// Response struct without the stupid optional types due to proto3 and a `TryFrom` impl that
+/// checks the `validate` rules defined in the proto file, failing with `Error::InvalidResponse`.
 #[derive(Clone, Debug, PartialEq)]
 pub struct ReadRelationshipsResponse {
     pub read_at: super::ZedToken,
@@ -71,13 +108,97 @@ pub struct ReadRelationshipsResponse {
     pub after_result_cursor: Option<super::Cursor>,
 }
 
-impl From<super::ReadRelationshipsResponse> for ReadRelationshipsResponse {
-    fn from(resp: super::ReadRelationshipsResponse) -> Self {
-        ReadRelationshipsResponse {
-            read_at: resp.read_at.unwrap(),
-            relationships: resp.relationship.into_iter().map(|r| r.into()).collect(),
+impl TryFrom<super::ReadRelationshipsResponse> for ReadRelationshipsResponse {
+    type Error = Error;
+
+    fn try_from(resp: super::ReadRelationshipsResponse) -> Result<Self, Self::Error> {
+        Ok(ReadRelationshipsResponse {
+            read_at: resp.read_at.ok_or_else(|| {
+                Error::invalid_response("ReadRelationshipsResponse without read_at")
+            })?,
+            relationships: resp
+                .relationship
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
             after_result_cursor: resp.after_result_cursor,
-        }
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RelationshipUpdate {
+    pub operation: crate::RelationshipOperation,
+    pub relationship: Relationship,
+}
+
+impl TryFrom<super::RelationshipUpdate> for RelationshipUpdate {
+    type Error = Error;
+
+    fn try_from(update: super::RelationshipUpdate) -> Result<Self, Self::Error> {
+        let operation = crate::RelationshipOperation::try_from(update.operation)
+            .ok()
+            .filter(|op| *op != crate::RelationshipOperation::Unspecified)
+            .ok_or_else(|| {
+                Error::invalid_response(format!(
+                    "Invalid i32 value for RelationshipOperation: {}",
+                    update.operation
+                ))
+            })?;
+        let relationship = update
+            .relationship
+            .ok_or_else(|| Error::invalid_response("RelationshipUpdate without a relationship"))?;
+        Ok(RelationshipUpdate {
+            operation,
+            relationship: relationship.try_into()?,
+        })
+    }
+}
+
+/// Wrapper struct for the WatchResponse, `changes_through` can be used to resume watching right
+/// after the updates contained in this response.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WatchResponse {
+    pub updates: Vec<RelationshipUpdate>,
+    pub changes_through: super::ZedToken,
+}
+
+impl TryFrom<super::WatchResponse> for WatchResponse {
+    type Error = Error;
+
+    fn try_from(resp: super::WatchResponse) -> Result<Self, Self::Error> {
+        Ok(WatchResponse {
+            updates: resp
+                .updates
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+            changes_through: resp
+                .changes_through
+                .ok_or_else(|| Error::invalid_response("WatchResponse without changes_through"))?,
+        })
+    }
+}
+
+/// One page of a bulk export, `after_result_cursor` resumes the export right after this page.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BulkExportRelationshipsResponse {
+    pub after_result_cursor: Option<super::Cursor>,
+    pub relationships: Vec<Relationship>,
+}
+
+impl TryFrom<super::BulkExportRelationshipsResponse> for BulkExportRelationshipsResponse {
+    type Error = Error;
+
+    fn try_from(resp: super::BulkExportRelationshipsResponse) -> Result<Self, Self::Error> {
+        Ok(BulkExportRelationshipsResponse {
+            after_result_cursor: resp.after_result_cursor,
+            relationships: resp
+                .relationships
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        })
     }
 }
 
@@ -97,11 +218,220 @@ pub struct ReadSchemaResponse {
     pub read_at: super::ZedToken,
 }
 
-impl From<super::ReadSchemaResponse> for ReadSchemaResponse {
-    fn from(resp: super::ReadSchemaResponse) -> Self {
-        ReadSchemaResponse {
+impl TryFrom<super::ReadSchemaResponse> for ReadSchemaResponse {
+    type Error = Error;
+
+    fn try_from(resp: super::ReadSchemaResponse) -> Result<Self, Self::Error> {
+        Ok(ReadSchemaResponse {
             schema_text: resp.schema_text,
-            read_at: resp.read_at.unwrap(),
+            read_at: resp
+                .read_at
+                .ok_or_else(|| Error::invalid_response("ReadSchemaResponse without read_at"))?,
+        })
+    }
+}
+
+/// Typed outcome of a single permission check
+#[derive(Clone, Debug, PartialEq)]
+pub enum Permissionship {
+    HasPermission,
+    NoPermission,
+    /// The check depends on a caveat whose required context was not provided
+    ConditionalPermission {
+        missing_caveats: Vec<String>,
+    },
+}
+
+impl Permissionship {
+    pub fn has_permission(&self) -> bool {
+        matches!(self, Permissionship::HasPermission)
+    }
+
+    fn try_from_raw(
+        permissionship: i32,
+        partial_caveat_info: Option<super::PartialCaveatInfo>,
+    ) -> Result<Self, Error> {
+        match CheckPermissionship::try_from(permissionship) {
+            Ok(CheckPermissionship::HasPermission) => Ok(Permissionship::HasPermission),
+            Ok(CheckPermissionship::NoPermission) => Ok(Permissionship::NoPermission),
+            Ok(CheckPermissionship::ConditionalPermission) => {
+                Ok(Permissionship::ConditionalPermission {
+                    missing_caveats: partial_caveat_info
+                        .map(|p| p.missing_required_context)
+                        .unwrap_or_default(),
+                })
+            }
+            _ => Err(Error::invalid_response(format!(
+                "Invalid i32 value for Permissionship: {}",
+                permissionship
+            ))),
+        }
+    }
+}
+
+/// Wrapper struct for the CheckBulkPermissionsResponse, `results` are in the same order as the
+/// items of the request and every item either succeeded or failed on its own.
+#[derive(Debug)]
+pub struct CheckBulkPermissionsResponse {
+    pub checked_at: Option<super::ZedToken>,
+    pub results: Vec<Result<Permissionship, Error>>,
+}
+
+impl From<super::CheckBulkPermissionsResponse> for CheckBulkPermissionsResponse {
+    fn from(resp: super::CheckBulkPermissionsResponse) -> Self {
+        let results = resp
+            .pairs
+            .into_iter()
+            .map(|pair| match pair.response {
+                Some(CheckBulkPermissionsPairResponse::Item(item)) => {
+                    Permissionship::try_from_raw(item.permissionship, item.partial_caveat_info)
+                }
+                Some(CheckBulkPermissionsPairResponse::Error(status)) => {
+                    Err(Error::from_rpc_status(status))
+                }
+                None => Err(Error::invalid_response(
+                    "CheckBulkPermissionsPair without a response",
+                )),
+            })
+            .collect();
+        CheckBulkPermissionsResponse {
+            checked_at: resp.checked_at,
+            results,
+        }
+    }
+}
+
+/// Node of an expanded permission, every node carries the object and relation it was expanded
+/// from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PermissionTree {
+    pub expanded_object: Option<super::ObjectReference>,
+    pub expanded_relation: String,
+    pub node: PermissionTreeNode,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PermissionTreeNode {
+    /// Subjects of any of the children
+    Union(Vec<PermissionTree>),
+    /// Subjects present in all of the children
+    Intersection(Vec<PermissionTree>),
+    /// Subjects of the first child that are in none of the others
+    Exclusion(Vec<PermissionTree>),
+    Leaf(Vec<SubjectReference>),
+}
+
+impl PermissionTree {
+    pub fn children(&self) -> &[PermissionTree] {
+        match &self.node {
+            PermissionTreeNode::Union(children)
+            | PermissionTreeNode::Intersection(children)
+            | PermissionTreeNode::Exclusion(children) => children,
+            PermissionTreeNode::Leaf(_) => &[],
         }
     }
+
+    /// Iterates depth-first over this node and all of its descendants.
+    pub fn iter(&self) -> PermissionTreeIter<'_> {
+        PermissionTreeIter { stack: vec![self] }
+    }
+
+    /// Collects the subjects of all leaves. This ignores the set operations, so a subject removed
+    /// by an exclusion or not present in every branch of an intersection is still returned.
+    pub fn leaf_subjects(&self) -> Vec<&SubjectReference> {
+        self.iter()
+            .flat_map(|tree| match &tree.node {
+                PermissionTreeNode::Leaf(subjects) => subjects.as_slice(),
+                _ => &[],
+            })
+            .collect()
+    }
+}
+
+impl<'a> IntoIterator for &'a PermissionTree {
+    type Item = &'a PermissionTree;
+    type IntoIter = PermissionTreeIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct PermissionTreeIter<'a> {
+    stack: Vec<&'a PermissionTree>,
+}
+
+impl<'a> Iterator for PermissionTreeIter<'a> {
+    type Item = &'a PermissionTree;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tree = self.stack.pop()?;
+        self.stack.extend(tree.children().iter().rev());
+        Some(tree)
+    }
+}
+
+impl TryFrom<super::PermissionRelationshipTree> for PermissionTree {
+    type Error = Error;
+
+    fn try_from(tree: super::PermissionRelationshipTree) -> Result<Self, Self::Error> {
+        let node = match tree.tree_type {
+            Some(TreeType::Intermediate(set)) => {
+                let children = set
+                    .children
+                    .into_iter()
+                    .map(PermissionTree::try_from)
+                    .collect::<Result<Vec<_>, _>>()?;
+                match AlgebraicOperation::try_from(set.operation) {
+                    Ok(AlgebraicOperation::Union) => PermissionTreeNode::Union(children),
+                    Ok(AlgebraicOperation::Intersection) => {
+                        PermissionTreeNode::Intersection(children)
+                    }
+                    Ok(AlgebraicOperation::Exclusion) => PermissionTreeNode::Exclusion(children),
+                    _ => {
+                        return Err(Error::invalid_response(format!(
+                            "Invalid i32 value for AlgebraicSubjectSet operation: {}",
+                            set.operation
+                        )))
+                    }
+                }
+            }
+            Some(TreeType::Leaf(set)) => PermissionTreeNode::Leaf(
+                set.subjects
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<_, _>>()?,
+            ),
+            None => {
+                return Err(Error::invalid_response(
+                    "PermissionRelationshipTree without a tree type",
+                ))
+            }
+        };
+        Ok(PermissionTree {
+            expanded_object: tree.expanded_object,
+            expanded_relation: tree.expanded_relation,
+            node,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExpandPermissionTreeResponse {
+    pub expanded_at: Option<super::ZedToken>,
+    pub tree: PermissionTree,
+}
+
+impl TryFrom<super::ExpandPermissionTreeResponse> for ExpandPermissionTreeResponse {
+    type Error = Error;
+
+    fn try_from(resp: super::ExpandPermissionTreeResponse) -> Result<Self, Self::Error> {
+        let tree = resp
+            .tree_root
+            .ok_or_else(|| Error::invalid_response("Expected a tree root"))?;
+        Ok(ExpandPermissionTreeResponse {
+            expanded_at: resp.expanded_at,
+            tree: tree.try_into()?,
+        })
+    }
 }
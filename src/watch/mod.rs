@@ -0,0 +1,11 @@
+mod watch_relationships;
+mod watcher;
+
+use crate::grpc::AuthenticatedChannel;
+use crate::spicedb;
+
+pub type SpiceDBWatchClient =
+    spicedb::watch_service_client::WatchServiceClient<AuthenticatedChannel>;
+
+pub use watch_relationships::WatchRequest;
+pub use watcher::{CheckpointStore, FileCheckpointStore, InMemoryCheckpointStore, Watcher};
@@ -0,0 +1,63 @@
+use tokio_stream::{Stream, StreamExt};
+
+use crate::grpc::GrpcResult;
+use crate::spicedb;
+use crate::spicedb::wrappers::WatchResponse;
+use crate::watch::SpiceDBWatchClient;
+use crate::Entity;
+use crate::Error;
+
+#[derive(Clone, Debug)]
+pub struct WatchRequest {
+    client: SpiceDBWatchClient,
+    request: spicedb::WatchRequest,
+}
+
+impl WatchRequest {
+    pub fn new(client: SpiceDBWatchClient) -> Self {
+        let request = spicedb::WatchRequest {
+            ..Default::default()
+        };
+        WatchRequest { client, request }
+    }
+
+    /// Only watch for changes to relationships on resources of type `E`, can't be combined with
+    /// relationship filters.
+    pub fn add_object_type<E>(&mut self) -> &mut Self
+    where
+        E: Entity,
+    {
+        self.add_object_type_raw(E::object_type())
+    }
+
+    pub fn add_object_type_raw(&mut self, object_type: impl Into<String>) -> &mut Self {
+        self.request.optional_object_types.push(object_type.into());
+        self
+    }
+
+    /// Only watch for changes to relationships matching at least one of the added filters, can't
+    /// be combined with object types.
+    pub fn add_relationship_filter(&mut self, filter: spicedb::RelationshipFilter) -> &mut Self {
+        self.request.optional_relationship_filters.push(filter);
+        self
+    }
+
+    /// Start watching from the point-in-time of the given `ZedToken`, usually the
+    /// `changes_through` token of a previous `WatchResponse`. Defaults to the current head revision.
+    pub fn with_start_cursor(&mut self, token: spicedb::ZedToken) -> &mut Self {
+        self.request.optional_start_cursor = Some(token);
+        self
+    }
+
+    pub async fn send(mut self) -> GrpcResult<impl Stream<Item = GrpcResult<WatchResponse>>> {
+        if !self.request.optional_object_types.is_empty()
+            && !self.request.optional_relationship_filters.is_empty()
+        {
+            return Err(Error::invalid_request(
+                "object types and relationship filters cannot be combined",
+            ));
+        }
+        let resp = self.client.watch(self.request).await?.into_inner();
+        Ok(resp.map(|r| r.map_err(Error::from).and_then(TryInto::try_into)))
+    }
+}
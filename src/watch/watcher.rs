@@ -0,0 +1,284 @@
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio_stream::{Stream, StreamExt};
+
+use crate::grpc::GrpcResult;
+use crate::spicedb;
+use crate::spicedb::wrappers::WatchResponse;
+use crate::watch::WatchRequest;
+use crate::Entity;
+use crate::Error;
+
+type WatchStream = Pin<Box<dyn Stream<Item = GrpcResult<WatchResponse>> + Send>>;
+
+/// Persists the last `changes_through` token seen by a `Watcher` so it can resume from there
+/// after a restart instead of starting at the current head revision.
+#[tonic::async_trait]
+pub trait CheckpointStore: Send + Sync {
+    async fn load(&self) -> anyhow::Result<Option<spicedb::ZedToken>>;
+    async fn save(&self, token: &spicedb::ZedToken) -> anyhow::Result<()>;
+}
+
+#[tonic::async_trait]
+impl<T> CheckpointStore for Arc<T>
+where
+    T: CheckpointStore + ?Sized,
+{
+    async fn load(&self) -> anyhow::Result<Option<spicedb::ZedToken>> {
+        (**self).load().await
+    }
+
+    async fn save(&self, token: &spicedb::ZedToken) -> anyhow::Result<()> {
+        (**self).save(token).await
+    }
+}
+
+/// Keeps the checkpoint in memory, survives reconnects but not restarts.
+#[derive(Debug, Default)]
+pub struct InMemoryCheckpointStore {
+    token: Mutex<Option<spicedb::ZedToken>>,
+}
+
+impl InMemoryCheckpointStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[tonic::async_trait]
+impl CheckpointStore for InMemoryCheckpointStore {
+    async fn load(&self) -> anyhow::Result<Option<spicedb::ZedToken>> {
+        Ok(self.token.lock().unwrap().clone())
+    }
+
+    async fn save(&self, token: &spicedb::ZedToken) -> anyhow::Result<()> {
+        *self.token.lock().unwrap() = Some(token.clone());
+        Ok(())
+    }
+}
+
+/// Stores the raw token string in a file, a missing or empty file means there is no checkpoint
+/// yet. Writes go to a temporary file next to it that is then renamed, so a crash mid-write never
+/// leaves a corrupted checkpoint behind.
+#[derive(Clone, Debug)]
+pub struct FileCheckpointStore {
+    path: PathBuf,
+}
+
+impl FileCheckpointStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[tonic::async_trait]
+impl CheckpointStore for FileCheckpointStore {
+    async fn load(&self) -> anyhow::Result<Option<spicedb::ZedToken>> {
+        match tokio::fs::read_to_string(&self.path).await {
+            Ok(token) if token.trim().is_empty() => Ok(None),
+            Ok(token) => Ok(Some(spicedb::ZedToken {
+                token: token.trim().to_owned(),
+            })),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn save(&self, token: &spicedb::ZedToken) -> anyhow::Result<()> {
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        tokio::fs::write(&tmp, &token.token).await?;
+        tokio::fs::rename(&tmp, &self.path).await?;
+        Ok(())
+    }
+}
+
+/// Long-lived watch that transparently reconnects from the last `changes_through` token when the
+/// stream drops, persisting that token in a `CheckpointStore`.
+///
+/// A checkpoint is only saved once the next item is polled from the stream, so an update is
+/// considered processed when the consumer asks for the one after it. After a crash the last
+/// unprocessed response is delivered again, nothing is skipped.
+pub struct Watcher<S> {
+    request: WatchRequest,
+    store: S,
+    reconnect_delay: Duration,
+    max_reconnect_delay: Duration,
+    max_reconnect_attempts: Option<u32>,
+}
+
+impl<S> Watcher<S>
+where
+    S: CheckpointStore + 'static,
+{
+    pub fn new(request: WatchRequest, store: S) -> Self {
+        Watcher {
+            request,
+            store,
+            reconnect_delay: Duration::from_millis(100),
+            max_reconnect_delay: Duration::from_secs(30),
+            max_reconnect_attempts: None,
+        }
+    }
+
+    pub fn add_object_type<E>(&mut self) -> &mut Self
+    where
+        E: Entity,
+    {
+        self.request.add_object_type::<E>();
+        self
+    }
+
+    pub fn add_object_type_raw(&mut self, object_type: impl Into<String>) -> &mut Self {
+        self.request.add_object_type_raw(object_type);
+        self
+    }
+
+    pub fn add_relationship_filter(&mut self, filter: spicedb::RelationshipFilter) -> &mut Self {
+        self.request.add_relationship_filter(filter);
+        self
+    }
+
+    /// Token to start from when the `CheckpointStore` is still empty.
+    pub fn with_start_cursor(&mut self, token: spicedb::ZedToken) -> &mut Self {
+        self.request.with_start_cursor(token);
+        self
+    }
+
+    /// Initial delay before reconnecting, doubled on every consecutive failed attempt.
+    pub fn reconnect_delay(&mut self, delay: Duration) -> &mut Self {
+        self.reconnect_delay = delay;
+        self
+    }
+
+    pub fn max_reconnect_delay(&mut self, delay: Duration) -> &mut Self {
+        self.max_reconnect_delay = delay;
+        self
+    }
+
+    /// Give up and yield the error after this many consecutive failed reconnects, unlimited by
+    /// default.
+    pub fn max_reconnect_attempts(&mut self, attempts: u32) -> &mut Self {
+        self.max_reconnect_attempts = Some(attempts);
+        self
+    }
+
+    /// Loads the checkpoint and starts watching. The stream ends after yielding the first error
+    /// that can't be recovered from by reconnecting.
+    pub async fn start(self) -> GrpcResult<impl Stream<Item = GrpcResult<WatchResponse>> + Send> {
+        let checkpoint = self.store.load().await.map_err(Error::Checkpoint)?;
+        let state = WatcherState {
+            cursor: checkpoint,
+            pending_checkpoint: None,
+            stream: None,
+            failed_attempts: 0,
+            done: false,
+            watcher: self,
+        };
+        Ok(futures::stream::unfold(state, WatcherState::next))
+    }
+}
+
+struct WatcherState<S> {
+    watcher: Watcher<S>,
+    cursor: Option<spicedb::ZedToken>,
+    pending_checkpoint: Option<spicedb::ZedToken>,
+    stream: Option<WatchStream>,
+    failed_attempts: u32,
+    done: bool,
+}
+
+impl<S> WatcherState<S>
+where
+    S: CheckpointStore + 'static,
+{
+    async fn next(mut self) -> Option<(GrpcResult<WatchResponse>, Self)> {
+        if self.done {
+            return None;
+        }
+        if let Some(token) = self.pending_checkpoint.take() {
+            if let Err(e) = self.watcher.store.save(&token).await {
+                self.done = true;
+                return Some((Err(Error::Checkpoint(e)), self));
+            }
+        }
+        loop {
+            if self.stream.is_none() {
+                let mut request = self.watcher.request.clone();
+                if let Some(cursor) = self.cursor.clone() {
+                    request.with_start_cursor(cursor);
+                }
+                match request.send().await {
+                    Ok(stream) => self.stream = Some(Box::pin(stream)),
+                    Err(err) => match self.backoff(err).await {
+                        Ok(()) => continue,
+                        Err(err) => return Some((Err(err), self)),
+                    },
+                }
+            }
+            let next = match self.stream.as_mut() {
+                Some(stream) => stream.next().await,
+                None => unreachable!(),
+            };
+            match next {
+                Some(Ok(resp)) => {
+                    self.failed_attempts = 0;
+                    self.cursor = Some(resp.changes_through.clone());
+                    self.pending_checkpoint = Some(resp.changes_through.clone());
+                    return Some((Ok(resp), self));
+                }
+                Some(Err(err)) => {
+                    self.stream = None;
+                    if let Err(err) = self.backoff(err).await {
+                        return Some((Err(err), self));
+                    }
+                }
+                None => {
+                    self.stream = None;
+                    let status = tonic::Status::unavailable("watch stream closed by server");
+                    if let Err(err) = self.backoff(status.into()).await {
+                        return Some((Err(err), self));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Waits before the next reconnect, or hands back the error if it is not worth retrying.
+    async fn backoff(&mut self, err: Error) -> GrpcResult<()> {
+        let exhausted = self
+            .watcher
+            .max_reconnect_attempts
+            .is_some_and(|max| self.failed_attempts >= max);
+        if !should_reconnect(&err) || exhausted {
+            self.done = true;
+            return Err(err);
+        }
+        let delay = self
+            .watcher
+            .reconnect_delay
+            .saturating_mul(2u32.saturating_pow(self.failed_attempts))
+            .min(self.watcher.max_reconnect_delay);
+        self.failed_attempts += 1;
+        tokio::time::sleep(delay).await;
+        Ok(())
+    }
+}
+
+/// Only failures reported by SpiceDB or the transport are retried, a response this crate can't
+/// convert fails the same way after reconnecting.
+fn should_reconnect(err: &Error) -> bool {
+    let from_server = err.status().is_some() || matches!(err, Error::Transport(_));
+    from_server
+        && matches!(
+            err.code(),
+            tonic::Code::Unavailable
+                | tonic::Code::Unknown
+                | tonic::Code::Internal
+                | tonic::Code::Aborted
+                | tonic::Code::DeadlineExceeded
+        )
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use std::io::Cursor;
+
+use common::stub::{token, Stub};
+use spicedb_rust::spicedb;
+
+#[tokio::test]
+async fn truncated_backups_are_rejected_before_writing_the_schema() {
+    let stub = Stub {
+        read_schema: Some(spicedb::ReadSchemaResponse {
+            schema_text: "definition user {}".to_owned(),
+            read_at: token(),
+        }),
+        ..Default::default()
+    };
+    let client = stub.serve().await;
+    let mut backup = Vec::new();
+    client.backup(&mut backup).await.unwrap();
+    assert_eq!(stub.calls(), 2);
+
+    // Cut right after the header and within the end marker of the relationships
+    for cut in [10, 8] {
+        let err = client
+            .restore(&mut Cursor::new(&backup[..backup.len() - cut]))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("truncated"), "{}", err);
+    }
+    assert_eq!(stub.calls(), 2);
+
+    // The stub doesn't implement `WriteSchema`, the intact backup gets as far as calling it
+    let err = client.restore(&mut Cursor::new(&backup)).await.unwrap_err();
+    assert!(err.to_string().contains("stub"), "{}", err);
+    assert_eq!(stub.calls(), 3);
+}
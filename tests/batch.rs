@@ -0,0 +1,101 @@
+mod common;
+
+use std::time::Duration;
+
+use common::stub::Stub;
+use common::{Document, DocumentPermission, User};
+use futures::future::join_all;
+use spicedb_rust::{RetryPolicy, SpiceDBClient};
+use tonic::Status;
+use uuid::Uuid;
+
+async fn batching_client(stub: &Stub, max_batch_size: usize) -> SpiceDBClient {
+    stub.serve_with(|b| {
+        b.check_batching(Duration::from_millis(20), max_batch_size);
+    })
+    .await
+}
+
+#[tokio::test]
+async fn concurrent_checks_are_sent_in_one_request() {
+    let stub = Stub::default();
+    let client = batching_client(&stub, 10).await;
+    let actor = User::new(Uuid::nil());
+
+    let ids = ["allowed-1", "denied-1", "allowed-2", "denied-2"];
+    let results = join_all(
+        ids.map(|id| client.check_permission::<Document>(&actor, id, DocumentPermission::Read)),
+    )
+    .await;
+
+    let results: Vec<bool> = results.into_iter().map(Result::unwrap).collect();
+    assert_eq!(results, vec![true, false, true, false]);
+    assert_eq!(*stub.check_bulk_sizes.lock().unwrap(), vec![4]);
+    assert!(stub.check_consistencies().is_empty());
+}
+
+#[tokio::test]
+async fn batches_are_split_at_max_batch_size() {
+    let stub = Stub::default();
+    let client = batching_client(&stub, 2).await;
+    let actor = User::new(Uuid::nil());
+
+    let results = join_all((0..5).map(|i| {
+        client.check_permission::<Document>(
+            &actor,
+            format!("allowed-{}", i),
+            DocumentPermission::Read,
+        )
+    }))
+    .await;
+
+    assert!(results.into_iter().all(|allowed| allowed.unwrap()));
+    let mut sizes = stub.check_bulk_sizes.lock().unwrap().clone();
+    sizes.sort();
+    assert_eq!(sizes, vec![1, 2, 2]);
+}
+
+#[tokio::test]
+async fn batch_failures_reach_every_caller() {
+    let stub = Stub::default().fail_with([Status::unavailable("down")]);
+    let client = batching_client(&stub, 10).await;
+    let actor = User::new(Uuid::nil());
+
+    let results =
+        join_all((0..3).map(|_| {
+            client.check_permission::<Document>(&actor, "allowed", DocumentPermission::Read)
+        }))
+        .await;
+
+    for result in results {
+        assert_eq!(result.unwrap_err().code(), tonic::Code::Unavailable);
+    }
+    assert_eq!(stub.calls(), 1);
+}
+
+#[tokio::test]
+async fn batches_are_sent_with_the_retry_policy_of_their_checks() {
+    let stub = Stub::default().fail_with([Status::unavailable("down")]);
+    let client = batching_client(&stub, 10).await;
+    let mut policy = RetryPolicy::default();
+    policy.initial_backoff(Duration::ZERO);
+    let retrying = client.with_retry_policy(policy);
+    let actor = User::new(Uuid::nil());
+
+    let (retried, other) = tokio::join!(
+        retrying.check_permission::<Document>(&actor, "allowed", DocumentPermission::Read),
+        async {
+            // Joins the batch of `retrying` unless the policies are told apart
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            client
+                .check_permission::<Document>(&actor, "allowed", DocumentPermission::Read)
+                .await
+        }
+    );
+
+    assert!(retried.unwrap());
+    assert!(other.unwrap());
+    let mut sizes = stub.check_bulk_sizes.lock().unwrap().clone();
+    sizes.sort();
+    assert_eq!(sizes, vec![1, 1, 1]);
+}
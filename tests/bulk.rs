@@ -0,0 +1,172 @@
+#![cfg(feature = "integration-test")]
+
+mod common;
+
+use std::io::Cursor;
+
+use common::{Document, DocumentRelation, User};
+use pretty_assertions::assert_eq;
+use spicedb_rust::spicedb::wrappers::Consistency;
+use spicedb_rust::spicedb::{object_reference, subject_reference, Relationship};
+use spicedb_rust::{Relation, SpiceDBClient};
+use tokio_stream::StreamExt;
+use uuid::Uuid;
+
+fn reader_relationship(user_id: Uuid, document_id: String) -> Relationship {
+    Relationship {
+        resource: Some(object_reference::<Document>(document_id)),
+        relation: DocumentRelation::Reader.name().to_owned(),
+        subject: Some(subject_reference::<User>(user_id, None)),
+        optional_caveat: None,
+    }
+}
+
+#[tokio::test]
+async fn bulk_import_relationships() {
+    let client = SpiceDBClient::new_isolated("http://localhost:50051")
+        .await
+        .unwrap();
+    let schema = include_str!("schema.zed");
+    client.write_schema(schema.to_owned()).await.unwrap();
+
+    let user_id = Uuid::now_v7();
+    let relationships =
+        (0..2500).map(move |i| reader_relationship(user_id, format!("document_{}", i)));
+    let mut request = client.bulk_import_relationships_request();
+    request.chunk_size(1000);
+    let num_loaded = request.send(relationships).await.unwrap();
+    assert_eq!(num_loaded, 2500);
+}
+
+#[tokio::test]
+async fn bulk_export_relationships_resumes_from_cursor() {
+    let client = SpiceDBClient::new_isolated("http://localhost:50051")
+        .await
+        .unwrap();
+    let schema = include_str!("schema.zed");
+    client.write_schema(schema.to_owned()).await.unwrap();
+
+    let user_id = Uuid::now_v7();
+    let relationships = (0..25).map(move |i| reader_relationship(user_id, format!("doc_{}", i)));
+    client
+        .bulk_import_relationships(relationships)
+        .await
+        .unwrap();
+
+    let mut request = client.bulk_export_relationships_request();
+    request.with_consistency(Consistency::FullyConsistent);
+    request.with_limit(10);
+    let mut pages = Box::pin(request.send_stream().await.unwrap());
+    let first_page = pages.next().await.unwrap().unwrap();
+    assert_eq!(first_page.relationships.len(), 10);
+    drop(pages);
+
+    let mut request = client.bulk_export_relationships_request();
+    request.with_consistency(Consistency::FullyConsistent);
+    request.with_limit(10);
+    request.with_cursor(first_page.after_result_cursor.unwrap());
+    let remaining = request
+        .send_relationships_stream()
+        .await
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .await
+        .unwrap();
+    assert_eq!(remaining.len(), 15);
+    assert!(remaining
+        .iter()
+        .all(|r| !first_page.relationships.contains(r)));
+
+    let all = client
+        .bulk_export_relationships(Consistency::FullyConsistent)
+        .await
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .await
+        .unwrap();
+    assert_eq!(all.len(), 25);
+}
+
+#[tokio::test]
+async fn backup_and_restore() {
+    let client = SpiceDBClient::new_isolated("http://localhost:50051")
+        .await
+        .unwrap();
+    let schema = include_str!("schema.zed");
+    client.write_schema(schema.to_owned()).await.unwrap();
+    let user_id = Uuid::now_v7();
+    let relationships = (0..25).map(move |i| reader_relationship(user_id, format!("doc_{}", i)));
+    client
+        .bulk_import_relationships(relationships)
+        .await
+        .unwrap();
+
+    let mut backup = Vec::new();
+    client.backup(&mut backup).await.unwrap();
+
+    let restored_client = SpiceDBClient::new_isolated("http://localhost:50051")
+        .await
+        .unwrap();
+    let num_loaded = restored_client
+        .restore(&mut Cursor::new(backup))
+        .await
+        .unwrap();
+    assert_eq!(num_loaded, 25);
+
+    let restored_schema = restored_client.read_schema().await.unwrap();
+    assert_eq!(
+        restored_schema.schema_text,
+        client.read_schema().await.unwrap().schema_text
+    );
+    let mut expected = client
+        .bulk_export_relationships(Consistency::FullyConsistent)
+        .await
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .await
+        .unwrap();
+    let mut restored = restored_client
+        .bulk_export_relationships(Consistency::FullyConsistent)
+        .await
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .await
+        .unwrap();
+    expected.sort_by_key(|r| r.resource.object_id.clone());
+    restored.sort_by_key(|r| r.resource.object_id.clone());
+    assert_eq!(restored, expected);
+}
+
+#[tokio::test]
+async fn truncated_backup_is_rejected() {
+    let client = SpiceDBClient::new_isolated("http://localhost:50051")
+        .await
+        .unwrap();
+    client
+        .write_schema(include_str!("schema.zed").to_owned())
+        .await
+        .unwrap();
+    let user_id = Uuid::now_v7();
+    let relationships = (0..5).map(move |i| reader_relationship(user_id, format!("doc_{}", i)));
+    client
+        .bulk_import_relationships(relationships)
+        .await
+        .unwrap();
+    let mut backup = Vec::new();
+    client.backup(&mut backup).await.unwrap();
+
+    // The backup ends with the 4 byte end marker and the 6 byte trailer frame, cut right after
+    // the last relationship and within the end marker
+    for cut in [10, 8] {
+        let restored_client = SpiceDBClient::new_isolated("http://localhost:50051")
+            .await
+            .unwrap();
+        let err = restored_client
+            .restore(&mut Cursor::new(&backup[..backup.len() - cut]))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("truncated"), "{}", err);
+        // Neither the schema nor any relationship was written
+        assert!(restored_client.read_schema().await.is_err());
+    }
+}
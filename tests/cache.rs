@@ -0,0 +1,163 @@
+mod common;
+
+use std::time::Duration;
+
+use common::stub::{token, Stub};
+use common::{Document, DocumentPermission, DocumentRelation, User};
+use spicedb_rust::spicedb::check_permission_response::Permissionship;
+use spicedb_rust::spicedb::{self, relationship_update};
+use spicedb_rust::{CachedSpiceDBClient, RelationshipOperation};
+use uuid::Uuid;
+
+fn stub(permissionship: Permissionship) -> Stub {
+    Stub {
+        check_permission: Some(spicedb::CheckPermissionResponse {
+            checked_at: token(),
+            permissionship: permissionship as i32,
+            ..Default::default()
+        }),
+        read_schema: Some(spicedb::ReadSchemaResponse {
+            schema_text: String::new(),
+            read_at: token(),
+        }),
+        ..Default::default()
+    }
+}
+
+async fn watching(cached: &CachedSpiceDBClient) {
+    for _ in 0..100 {
+        if cached.stats().watching {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("the watch didn't start");
+}
+
+fn changed(resource_type: &str) -> spicedb::WatchResponse {
+    let mut update = relationship_update::<User, Document>(
+        RelationshipOperation::Touch,
+        Uuid::nil(),
+        None,
+        "readme",
+        DocumentRelation::Reader,
+    );
+    if let Some(resource) = update
+        .relationship
+        .as_mut()
+        .and_then(|r| r.resource.as_mut())
+    {
+        resource.object_type = resource_type.to_owned();
+    }
+    spicedb::WatchResponse {
+        updates: vec![update],
+        changes_through: token(),
+    }
+}
+
+#[tokio::test]
+async fn results_are_cached_until_their_resource_type_changes() {
+    let stub = stub(Permissionship::HasPermission);
+    let changes = stub.watch_channel();
+    let cached = CachedSpiceDBClient::builder(stub.serve().await).build();
+    watching(&cached).await;
+    let actor = User::new(Uuid::nil());
+    let check = || cached.check_permission::<Document>(&actor, "readme", DocumentPermission::Read);
+
+    assert!(check().await.unwrap());
+    assert!(check().await.unwrap());
+    assert_eq!(stub.check_consistencies().len(), 1);
+
+    changes.send(changed("user")).unwrap();
+    changes.send(changed("document")).unwrap();
+    for _ in 0..100 {
+        if cached.stats().invalidations > 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(check().await.unwrap());
+    assert_eq!(stub.check_consistencies().len(), 2);
+
+    let stats = cached.stats();
+    assert_eq!((stats.hits, stats.misses), (1, 2));
+    assert_eq!((stats.entries, stats.invalidations), (1, 1));
+    assert!(stats.watching);
+}
+
+#[tokio::test]
+async fn conditional_results_are_not_cached() {
+    let stub = stub(Permissionship::ConditionalPermission);
+    let cached = CachedSpiceDBClient::builder(stub.serve().await).build();
+    let actor = User::new(Uuid::nil());
+
+    for _ in 0..2 {
+        let allowed = cached
+            .check_permission_with_context::<Document>(
+                &actor,
+                "readme",
+                DocumentPermission::Read,
+                prost_types::Struct::default(),
+            )
+            .await
+            .unwrap();
+        assert!(!allowed);
+    }
+    assert_eq!(stub.check_consistencies().len(), 2);
+    assert_eq!(cached.stats().entries, 0);
+}
+
+#[tokio::test]
+async fn entries_expire_and_are_bounded() {
+    let stub = stub(Permissionship::NoPermission);
+    let client = stub.serve().await;
+    let actor = User::new(Uuid::nil());
+
+    let cached = CachedSpiceDBClient::builder(client.clone())
+        .max_entries(1)
+        .build();
+    watching(&cached).await;
+    for id in ["a", "b", "a"] {
+        cached
+            .check_permission::<Document>(&actor, id, DocumentPermission::Read)
+            .await
+            .unwrap();
+    }
+    assert_eq!(stub.check_consistencies().len(), 3);
+    assert_eq!(cached.stats().entries, 1);
+
+    let cached = CachedSpiceDBClient::builder(client)
+        .ttl(Duration::from_millis(1))
+        .build();
+    watching(&cached).await;
+    for _ in 0..2 {
+        cached
+            .check_permission::<Document>(&actor, "a", DocumentPermission::Read)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert_eq!(stub.check_consistencies().len(), 5);
+    assert_eq!(cached.stats().hits, 0);
+}
+
+#[tokio::test]
+async fn nothing_is_cached_without_a_watch() {
+    let stub = Stub {
+        read_schema: None,
+        ..stub(Permissionship::HasPermission)
+    };
+    let cached = CachedSpiceDBClient::builder(stub.serve().await).build();
+    let actor = User::new(Uuid::nil());
+
+    for _ in 0..2 {
+        assert!(cached
+            .check_permission::<Document>(&actor, "readme", DocumentPermission::Read)
+            .await
+            .unwrap());
+    }
+    assert_eq!(stub.check_consistencies().len(), 2);
+    let stats = cached.stats();
+    assert_eq!(stats.entries, 0);
+    assert!(!stats.watching);
+}
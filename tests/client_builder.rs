@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use spicedb_rust::{Error, SpiceDBClient};
+
+/// Address nothing listens on
+async fn unused_addr() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    format!("http://{}", listener.local_addr().unwrap())
+}
+
+#[tokio::test]
+async fn lazy_client_fails_on_first_request() {
+    let addr = unused_addr().await;
+    assert!(SpiceDBClient::builder(&addr, "token")
+        .connect()
+        .await
+        .is_err());
+
+    let client = SpiceDBClient::builder(&addr, "token")
+        .lazy(true)
+        .connect_timeout(Duration::from_secs(1))
+        .timeout(Duration::from_secs(1))
+        .keepalive_interval(Duration::from_secs(30))
+        .user_agent("spicedb-rust-tests")
+        .connect()
+        .await
+        .unwrap();
+    let Err(err) = client.read_schema().await else {
+        panic!("nothing listens on {}", addr);
+    };
+    assert_eq!(err.code(), tonic::Code::Unavailable, "{:?}", err);
+}
+
+#[tokio::test]
+async fn invalid_options_are_rejected() {
+    let err = SpiceDBClient::builder("http://localhost:50051", "invalid\ntoken")
+        .lazy(true)
+        .connect()
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::InvalidRequest(_)), "{:?}", err);
+
+    let err = SpiceDBClient::builder("not a uri", "token")
+        .lazy(true)
+        .connect()
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::InvalidRequest(_)), "{:?}", err);
+}
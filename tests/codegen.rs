@@ -0,0 +1,135 @@
+use pretty_assertions::assert_eq;
+use spicedb_rust::codegen::Codegen;
+use spicedb_rust::spicedb::relationship_update;
+use spicedb_rust::{Caveat, Entity, IntoEnumIterator, Permission, Relation, RelationshipOperation};
+
+mod generated {
+    include!("generated/codegen.rs");
+}
+
+use generated::{
+    Document, DocumentPermission, DocumentRelation, IpAllowlist, IpAllowlistContext, OrgTeam,
+    OrgTeamRelation, User,
+};
+
+#[test]
+fn generated_code_is_up_to_date() {
+    let code = Codegen::new()
+        .id_type("user", "uuid::Uuid")
+        .render_zed(include_str!("codegen.zed"))
+        .unwrap();
+    assert_eq!(code, include_str!("generated/codegen.rs"));
+}
+
+#[test]
+fn generated_entities() {
+    assert_eq!(User::object_type(), "user");
+    assert_eq!(OrgTeam::object_type(), "org/team");
+    assert_eq!(Document::object_type(), "document");
+    assert_eq!(
+        DocumentRelation::iter()
+            .map(Relation::name)
+            .collect::<Vec<_>>(),
+        vec!["reader", "writer"]
+    );
+    assert_eq!(
+        DocumentPermission::iter()
+            .map(Permission::name)
+            .collect::<Vec<_>>(),
+        vec!["read", "write", "type"]
+    );
+}
+
+#[test]
+fn relation_only_definitions_are_resources() {
+    let code = Codegen::new()
+        .render_zed("definition user {}\ndefinition group { relation member: user }")
+        .unwrap();
+    assert!(code.contains(
+        "impl spicedb_rust::Resource for Group {\n    type Permissions = spicedb_rust::NoPermissions;"
+    ));
+    assert!(!code.contains("impl spicedb_rust::Resource for User"));
+
+    let update = relationship_update::<User, OrgTeam>(
+        RelationshipOperation::Touch,
+        uuid::Uuid::nil(),
+        None,
+        "engineering",
+        OrgTeamRelation::Member,
+    );
+    let relationship = update.relationship.unwrap();
+    assert_eq!(relationship.resource.unwrap().object_type, "org/team");
+    assert_eq!(relationship.relation, "member");
+}
+
+#[test]
+fn generated_caveat_context() {
+    assert_eq!(IpAllowlist::name(), "ip_allowlist");
+    let context: prost_types::Struct = IpAllowlistContext {
+        user_ip: Some("10.0.0.1".to_owned()),
+        allowed: Some(vec!["10.0.0.0/8".to_owned()]),
+        type_: None,
+    }
+    .into();
+    assert_eq!(context.fields.len(), 2);
+    assert_eq!(
+        context.fields["user_ip"].kind,
+        Some(prost_types::value::Kind::StringValue("10.0.0.1".to_owned()))
+    );
+}
+
+#[test]
+fn invalid_schema_is_rejected() {
+    let err = Codegen::new()
+        .render_zed("definition document { relation reader user }")
+        .unwrap_err();
+    assert!(err.to_string().contains("Expected `:`"), "{}", err);
+}
+
+#[test]
+fn keywords_get_a_suffix() {
+    let code = Codegen::new()
+        .render_zed(
+            "definition self { relation box: self }
+caveat try(crate int, macro string) { crate > 0 }",
+        )
+        .unwrap();
+    assert!(code.contains("pub struct Self_;"), "{}", code);
+    assert!(
+        code.contains("pub enum SelfRelation {\n    Box,\n}"),
+        "{}",
+        code
+    );
+    assert!(code.contains("pub struct Try;"), "{}", code);
+    assert!(code.contains("pub crate_: Option<i64>,"), "{}", code);
+    assert!(code.contains("pub macro_: Option<String>,"), "{}", code);
+}
+
+#[test]
+fn colliding_names_are_rejected() {
+    let collides = |schema| Codegen::new().render_zed(schema).unwrap_err().to_string();
+    assert_eq!(
+        collides("definition org/team {}\ndefinition org_team {}"),
+        "definition `org/team` and definition `org_team` both generate `OrgTeam`"
+    );
+    assert_eq!(
+        collides(
+            "definition document_relation {}
+definition document { relation reader: document_relation }"
+        ),
+        "definition `document_relation` and definition `document` both generate `DocumentRelation`"
+    );
+    assert_eq!(
+        collides("definition document { relation a_b: document\nrelation a__b: document }"),
+        "relation `document#a_b` and relation `document#a__b` both generate `AB`"
+    );
+    assert_eq!(
+        collides("caveat allowed(type int, type_ int) { type > 0 }"),
+        "parameter `type` of caveat `allowed` and parameter `type_` of caveat `allowed` both \
+         generate `type_`"
+    );
+    assert_eq!(
+        collides("definition string {}"),
+        "definition `string` generates `String`, which shadows the prelude type"
+    );
+}
@@ -0,0 +1,35 @@
+#![cfg(feature = "integration-test")]
+
+use spicedb_rust::codegen::Codegen;
+use spicedb_rust::SpiceDBClient;
+
+#[tokio::test]
+async fn generate_from_reflected_schema() {
+    let client = SpiceDBClient::new_isolated("http://localhost:50051")
+        .await
+        .unwrap();
+    client
+        .write_schema(include_str!("codegen.zed").to_owned())
+        .await
+        .unwrap();
+
+    let code = Codegen::new()
+        .id_type("user", "uuid::Uuid")
+        .render_from_client(&client)
+        .await
+        .unwrap();
+    for expected in [
+        "impl spicedb_rust::Entity for OrgTeam {",
+        "    type Id = uuid::Uuid;",
+        "impl spicedb_rust::Resource for Document {",
+        "            DocumentPermission::Type => \"type\",",
+        "    pub allowed: Option<Vec<String>>,",
+    ] {
+        assert!(
+            code.contains(expected),
+            "missing `{}` in:\n{}",
+            expected,
+            code
+        );
+    }
+}
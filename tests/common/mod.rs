@@ -0,0 +1,60 @@
+#![allow(dead_code)]
+
+use spicedb_rust::spicedb::{subject_reference_raw, SubjectReference};
+use spicedb_rust::{Actor, Entity, NoRelations, Resource};
+use spicedb_rust::{EnumIter, IntoStaticStr};
+use uuid::Uuid;
+
+pub mod stub;
+
+pub struct User(pub Uuid);
+
+impl User {
+    pub fn new(id: Uuid) -> Self {
+        Self(id)
+    }
+}
+
+impl Entity for User {
+    type Relations = NoRelations;
+    type Id = Uuid;
+
+    fn object_type() -> &'static str {
+        "user"
+    }
+}
+
+impl Actor for User {
+    fn to_subject(&self) -> SubjectReference {
+        subject_reference_raw(self.0, User::object_type(), None::<String>)
+    }
+}
+
+pub struct Document;
+
+#[derive(IntoStaticStr, EnumIter)]
+#[strum(serialize_all = "snake_case")]
+pub enum DocumentPermission {
+    Read,
+    Write,
+}
+
+impl Entity for Document {
+    type Relations = DocumentRelation;
+    type Id = String;
+
+    fn object_type() -> &'static str {
+        "document"
+    }
+}
+
+#[derive(IntoStaticStr, EnumIter)]
+#[strum(serialize_all = "snake_case")]
+pub enum DocumentRelation {
+    Reader,
+    Writer,
+}
+
+impl Resource for Document {
+    type Permissions = DocumentPermission;
+}
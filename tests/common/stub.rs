@@ -0,0 +1,335 @@
+//! In-process SpiceDB stand-in answering with canned responses, for tests that need responses a
+//! real SpiceDB doesn't send.
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use spicedb_rust::spicedb::check_bulk_permissions_pair::Response as Pair;
+use spicedb_rust::spicedb::check_permission_response::Permissionship;
+use spicedb_rust::spicedb::experimental_service_server::{
+    ExperimentalService, ExperimentalServiceServer,
+};
+use spicedb_rust::spicedb::permissions_service_server::{
+    PermissionsService, PermissionsServiceServer,
+};
+use spicedb_rust::spicedb::schema_service_server::{SchemaService, SchemaServiceServer};
+use spicedb_rust::spicedb::watch_service_server::{WatchService, WatchServiceServer};
+use spicedb_rust::spicedb::{self, ZedToken};
+use spicedb_rust::{SpiceDBClient, SpiceDBClientBuilder};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{TcpListenerStream, UnboundedReceiverStream};
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+
+type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+fn stream<T: Send + 'static>(items: Vec<T>) -> Response<ResponseStream<T>> {
+    Response::new(Box::pin(tokio_stream::iter(items.into_iter().map(Ok))))
+}
+
+pub fn token() -> Option<ZedToken> {
+    Some(ZedToken {
+        token: "token".to_owned(),
+    })
+}
+
+/// Answers every request with the canned responses, unset ones are `Unimplemented`. The first
+/// requests fail with `failures`, one each.
+#[derive(Clone, Default)]
+pub struct Stub {
+    pub read_relationships: Vec<spicedb::ReadRelationshipsResponse>,
+    pub lookup_resources: Vec<spicedb::LookupResourcesResponse>,
+    pub lookup_subjects: Vec<spicedb::LookupSubjectsResponse>,
+    pub check_permission: Option<spicedb::CheckPermissionResponse>,
+    pub write_relationships: Option<spicedb::WriteRelationshipsResponse>,
+    pub delete_relationships: Option<spicedb::DeleteRelationshipsResponse>,
+    pub read_schema: Option<spicedb::ReadSchemaResponse>,
+    pub watch: Vec<spicedb::WatchResponse>,
+    /// Streamed by the next `Watch` instead of `watch`, see `watch_channel`
+    pub watch_receiver: Arc<Mutex<Option<mpsc::UnboundedReceiver<spicedb::WatchResponse>>>>,
+    pub failures: Arc<Mutex<VecDeque<Status>>>,
+    /// Waited before answering `CheckPermission` and `LookupSubjects`, so concurrent requests
+    /// overlap
+    pub delay: Duration,
+    /// Number of requests received
+    pub calls: Arc<AtomicUsize>,
+    /// Number of items of every `CheckBulkPermissions` received, items on resources whose id
+    /// starts with `allowed` have the permission
+    pub check_bulk_sizes: Arc<Mutex<Vec<usize>>>,
+    /// Consistency of every `CheckPermission` received
+    pub check_consistencies: Arc<Mutex<Vec<Option<spicedb::Consistency>>>>,
+    /// Consistency of every `BulkExportRelationships` received, which answers with no pages
+    pub export_consistencies: Arc<Mutex<Vec<Option<spicedb::Consistency>>>>,
+}
+
+impl Stub {
+    pub fn fail_with(self, failures: impl IntoIterator<Item = Status>) -> Self {
+        self.failures.lock().unwrap().extend(failures);
+        self
+    }
+
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+
+    /// Sender of the responses of the next `Watch`, which stays open until the sender is dropped.
+    pub fn watch_channel(&self) -> mpsc::UnboundedSender<spicedb::WatchResponse> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        *self.watch_receiver.lock().unwrap() = Some(receiver);
+        sender
+    }
+
+    pub fn check_consistencies(&self) -> Vec<Option<spicedb::Consistency>> {
+        self.check_consistencies.lock().unwrap().clone()
+    }
+
+    // Same error type as the service traits
+    #[allow(clippy::result_large_err)]
+    fn answer<T>(&self, resp: Option<T>) -> Result<Response<T>, Status> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if let Some(status) = self.failures.lock().unwrap().pop_front() {
+            return Err(status);
+        }
+        resp.map(Response::new)
+            .ok_or_else(|| Status::unimplemented("stub"))
+    }
+
+    /// Serves the stub on a random local port, `configure` adjusts the client before connecting.
+    pub async fn serve_with(
+        &self,
+        configure: impl FnOnce(&mut SpiceDBClientBuilder),
+    ) -> SpiceDBClient {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(PermissionsServiceServer::new(self.clone()))
+                .add_service(SchemaServiceServer::new(self.clone()))
+                .add_service(WatchServiceServer::new(self.clone()))
+                .add_service(ExperimentalServiceServer::new(self.clone()))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        let mut builder = SpiceDBClient::builder(format!("http://{}", addr), "stub");
+        configure(&mut builder);
+        builder.connect().await.unwrap()
+    }
+
+    pub async fn serve(&self) -> SpiceDBClient {
+        self.serve_with(|_| {}).await
+    }
+}
+
+#[tonic::async_trait]
+impl PermissionsService for Stub {
+    type ReadRelationshipsStream = ResponseStream<spicedb::ReadRelationshipsResponse>;
+    type LookupResourcesStream = ResponseStream<spicedb::LookupResourcesResponse>;
+    type LookupSubjectsStream = ResponseStream<spicedb::LookupSubjectsResponse>;
+
+    async fn read_relationships(
+        &self,
+        _: Request<spicedb::ReadRelationshipsRequest>,
+    ) -> Result<Response<Self::ReadRelationshipsStream>, Status> {
+        self.answer(Some(()))?;
+        Ok(stream(self.read_relationships.clone()))
+    }
+
+    async fn write_relationships(
+        &self,
+        _: Request<spicedb::WriteRelationshipsRequest>,
+    ) -> Result<Response<spicedb::WriteRelationshipsResponse>, Status> {
+        self.answer(self.write_relationships.clone())
+    }
+
+    async fn delete_relationships(
+        &self,
+        _: Request<spicedb::DeleteRelationshipsRequest>,
+    ) -> Result<Response<spicedb::DeleteRelationshipsResponse>, Status> {
+        self.answer(self.delete_relationships.clone())
+    }
+
+    async fn check_permission(
+        &self,
+        request: Request<spicedb::CheckPermissionRequest>,
+    ) -> Result<Response<spicedb::CheckPermissionResponse>, Status> {
+        tokio::time::sleep(self.delay).await;
+        self.check_consistencies
+            .lock()
+            .unwrap()
+            .push(request.into_inner().consistency);
+        self.answer(self.check_permission.clone())
+    }
+
+    async fn check_bulk_permissions(
+        &self,
+        request: Request<spicedb::CheckBulkPermissionsRequest>,
+    ) -> Result<Response<spicedb::CheckBulkPermissionsResponse>, Status> {
+        let items = request.into_inner().items;
+        self.check_bulk_sizes.lock().unwrap().push(items.len());
+        let pairs = items
+            .into_iter()
+            .map(|item| {
+                let allowed = item
+                    .resource
+                    .as_ref()
+                    .is_some_and(|r| r.object_id.starts_with("allowed"));
+                let permissionship = if allowed {
+                    Permissionship::HasPermission
+                } else {
+                    Permissionship::NoPermission
+                };
+                spicedb::CheckBulkPermissionsPair {
+                    request: Some(item),
+                    response: Some(Pair::Item(spicedb::CheckBulkPermissionsResponseItem {
+                        permissionship: permissionship as i32,
+                        partial_caveat_info: None,
+                    })),
+                }
+            })
+            .collect();
+        self.answer(Some(spicedb::CheckBulkPermissionsResponse {
+            checked_at: token(),
+            pairs,
+        }))
+    }
+
+    async fn expand_permission_tree(
+        &self,
+        _: Request<spicedb::ExpandPermissionTreeRequest>,
+    ) -> Result<Response<spicedb::ExpandPermissionTreeResponse>, Status> {
+        self.answer(None)
+    }
+
+    async fn lookup_resources(
+        &self,
+        _: Request<spicedb::LookupResourcesRequest>,
+    ) -> Result<Response<Self::LookupResourcesStream>, Status> {
+        self.answer(Some(()))?;
+        Ok(stream(self.lookup_resources.clone()))
+    }
+
+    async fn lookup_subjects(
+        &self,
+        _: Request<spicedb::LookupSubjectsRequest>,
+    ) -> Result<Response<Self::LookupSubjectsStream>, Status> {
+        tokio::time::sleep(self.delay).await;
+        self.answer(Some(()))?;
+        Ok(stream(self.lookup_subjects.clone()))
+    }
+}
+
+#[tonic::async_trait]
+impl SchemaService for Stub {
+    async fn read_schema(
+        &self,
+        _: Request<spicedb::ReadSchemaRequest>,
+    ) -> Result<Response<spicedb::ReadSchemaResponse>, Status> {
+        self.answer(self.read_schema.clone())
+    }
+
+    async fn write_schema(
+        &self,
+        _: Request<spicedb::WriteSchemaRequest>,
+    ) -> Result<Response<spicedb::WriteSchemaResponse>, Status> {
+        self.answer(None)
+    }
+}
+
+#[tonic::async_trait]
+impl WatchService for Stub {
+    type WatchStream = ResponseStream<spicedb::WatchResponse>;
+
+    async fn watch(
+        &self,
+        _: Request<spicedb::WatchRequest>,
+    ) -> Result<Response<Self::WatchStream>, Status> {
+        self.answer(Some(()))?;
+        if let Some(receiver) = self.watch_receiver.lock().unwrap().take() {
+            let updates = UnboundedReceiverStream::new(receiver).map(Ok);
+            return Ok(Response::new(Box::pin(updates)));
+        }
+        Ok(stream(self.watch.clone()))
+    }
+}
+
+#[tonic::async_trait]
+impl ExperimentalService for Stub {
+    type BulkExportRelationshipsStream = ResponseStream<spicedb::BulkExportRelationshipsResponse>;
+
+    async fn bulk_import_relationships(
+        &self,
+        _: Request<tonic::Streaming<spicedb::BulkImportRelationshipsRequest>>,
+    ) -> Result<Response<spicedb::BulkImportRelationshipsResponse>, Status> {
+        self.answer(None)
+    }
+
+    async fn bulk_export_relationships(
+        &self,
+        request: Request<spicedb::BulkExportRelationshipsRequest>,
+    ) -> Result<Response<Self::BulkExportRelationshipsStream>, Status> {
+        self.export_consistencies
+            .lock()
+            .unwrap()
+            .push(request.into_inner().consistency);
+        self.answer(Some(()))?;
+        Ok(stream(vec![]))
+    }
+
+    async fn bulk_check_permission(
+        &self,
+        _: Request<spicedb::BulkCheckPermissionRequest>,
+    ) -> Result<Response<spicedb::BulkCheckPermissionResponse>, Status> {
+        self.answer(None)
+    }
+
+    async fn experimental_reflect_schema(
+        &self,
+        _: Request<spicedb::ExperimentalReflectSchemaRequest>,
+    ) -> Result<Response<spicedb::ExperimentalReflectSchemaResponse>, Status> {
+        self.answer(None)
+    }
+
+    async fn experimental_computable_permissions(
+        &self,
+        _: Request<spicedb::ExperimentalComputablePermissionsRequest>,
+    ) -> Result<Response<spicedb::ExperimentalComputablePermissionsResponse>, Status> {
+        self.answer(None)
+    }
+
+    async fn experimental_dependent_relations(
+        &self,
+        _: Request<spicedb::ExperimentalDependentRelationsRequest>,
+    ) -> Result<Response<spicedb::ExperimentalDependentRelationsResponse>, Status> {
+        self.answer(None)
+    }
+
+    async fn experimental_diff_schema(
+        &self,
+        _: Request<spicedb::ExperimentalDiffSchemaRequest>,
+    ) -> Result<Response<spicedb::ExperimentalDiffSchemaResponse>, Status> {
+        self.answer(None)
+    }
+
+    async fn experimental_register_relationship_counter(
+        &self,
+        _: Request<spicedb::ExperimentalRegisterRelationshipCounterRequest>,
+    ) -> Result<Response<spicedb::ExperimentalRegisterRelationshipCounterResponse>, Status> {
+        self.answer(None)
+    }
+
+    async fn experimental_count_relationships(
+        &self,
+        _: Request<spicedb::ExperimentalCountRelationshipsRequest>,
+    ) -> Result<Response<spicedb::ExperimentalCountRelationshipsResponse>, Status> {
+        self.answer(None)
+    }
+
+    async fn experimental_unregister_relationship_counter(
+        &self,
+        _: Request<spicedb::ExperimentalUnregisterRelationshipCounterRequest>,
+    ) -> Result<Response<spicedb::ExperimentalUnregisterRelationshipCounterResponse>, Status> {
+        self.answer(None)
+    }
+}
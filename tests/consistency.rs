@@ -0,0 +1,156 @@
+mod common;
+
+use common::stub::{token, Stub};
+use common::{Document, DocumentPermission, DocumentRelation, User};
+use spicedb_rust::spicedb::wrappers::Consistency;
+use spicedb_rust::spicedb::{self, relationship_update, ZedToken};
+use spicedb_rust::RelationshipOperation;
+use tokio_stream::StreamExt;
+use uuid::Uuid;
+
+fn stub() -> Stub {
+    Stub {
+        write_relationships: Some(spicedb::WriteRelationshipsResponse {
+            written_at: token(),
+        }),
+        check_permission: Some(spicedb::CheckPermissionResponse {
+            checked_at: token(),
+            permissionship: spicedb::check_permission_response::Permissionship::HasPermission
+                as i32,
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn client_default_applies_to_reads_without_consistency() {
+    let stub = stub();
+    let client = stub
+        .serve_with(|b| {
+            b.consistency(Consistency::FullyConsistent);
+        })
+        .await;
+    let actor = User::new(Uuid::nil());
+    let explicit = ZedToken {
+        token: "explicit".to_owned(),
+    };
+
+    client
+        .check_permission::<Document>(&actor, "readme", DocumentPermission::Read)
+        .await
+        .unwrap();
+    client
+        .check_permission_at::<Document>(
+            &actor,
+            "readme",
+            DocumentPermission::Read,
+            explicit.clone(),
+        )
+        .await
+        .unwrap();
+    client
+        .with_consistency(Consistency::MinimizeLatency)
+        .check_permission::<Document>(&actor, "readme", DocumentPermission::Read)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        stub.check_consistencies(),
+        vec![
+            Some(Consistency::FullyConsistent.into()),
+            Some(Consistency::AtLeastAsFresh(explicit).into()),
+            Some(Consistency::MinimizeLatency.into()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn session_token_takes_precedence_over_default() {
+    let stub = stub();
+    let client = stub
+        .serve()
+        .await
+        .with_consistency(Consistency::MinimizeLatency);
+    let actor = User::new(Uuid::nil());
+
+    let session = client.session("alice");
+    let written = session
+        .create_relationships(
+            [relationship_update::<User, Document>(
+                RelationshipOperation::Touch,
+                Uuid::nil(),
+                None,
+                "readme",
+                DocumentRelation::Reader,
+            )],
+            [],
+        )
+        .await
+        .unwrap();
+    session
+        .check_permission::<Document>(&actor, "readme", DocumentPermission::Read)
+        .await
+        .unwrap();
+    client
+        .check_permission::<Document>(&actor, "readme", DocumentPermission::Read)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        stub.check_consistencies(),
+        vec![
+            Some(Consistency::AtLeastAsFresh(written).into()),
+            Some(Consistency::MinimizeLatency.into()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn bulk_export_uses_default_and_session_token() {
+    let stub = stub();
+    let client = stub
+        .serve_with(|b| {
+            b.consistency(Consistency::FullyConsistent);
+        })
+        .await;
+    let session = client.session("alice");
+    let written = session
+        .create_relationships(
+            [relationship_update::<User, Document>(
+                RelationshipOperation::Touch,
+                Uuid::nil(),
+                None,
+                "readme",
+                DocumentRelation::Reader,
+            )],
+            [],
+        )
+        .await
+        .unwrap();
+
+    for client in [&client, &session] {
+        let exported = client
+            .bulk_export_relationships_request()
+            .send_relationships_stream()
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+        assert!(exported.is_empty());
+    }
+    let explicit = client
+        .bulk_export_relationships(Consistency::MinimizeLatency)
+        .await
+        .unwrap();
+    assert!(explicit.collect::<Vec<_>>().await.is_empty());
+
+    assert_eq!(
+        *stub.export_consistencies.lock().unwrap(),
+        vec![
+            Some(Consistency::FullyConsistent.into()),
+            Some(Consistency::AtLeastAsFresh(written).into()),
+            Some(Consistency::MinimizeLatency.into()),
+        ]
+    );
+}
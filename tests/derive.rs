@@ -0,0 +1,92 @@
+#![cfg(feature = "derive")]
+
+use pretty_assertions::assert_eq;
+use spicedb_rust::spicedb::{subject_reference_raw, SubjectReference};
+use spicedb_rust::{Actor, Entity, IntoStaticStr, NoRelations, Resource};
+use uuid::Uuid;
+
+#[derive(Entity, Actor)]
+#[spicedb(object_type = "user", id = Uuid)]
+struct User(Uuid);
+
+#[derive(Entity)]
+#[spicedb(object_type = "organization", id = String, relations = OrganizationRelation)]
+struct Organization;
+
+#[derive(IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+enum OrganizationRelation {
+    Member,
+}
+
+#[derive(Entity)]
+#[spicedb(
+    object_type = "document",
+    id = String,
+    relations = DocumentRelation,
+    permissions = DocumentPermission
+)]
+struct Document;
+
+#[derive(IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+enum DocumentRelation {
+    Reader,
+}
+
+#[derive(IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+enum DocumentPermission {
+    Read,
+}
+
+#[derive(Actor)]
+enum SystemActor {
+    #[spicedb(entity = User)]
+    User(Uuid),
+    #[spicedb(entity = Organization, relation = "member")]
+    OrganizationMember { id: String },
+}
+
+fn assert_types<E>()
+where
+    E: Resource<Id = String, Relations = DocumentRelation, Permissions = DocumentPermission>,
+{
+}
+
+#[test]
+fn derive_entity() {
+    assert_eq!(User::object_type(), "user");
+    assert_eq!(Organization::object_type(), "organization");
+    assert_eq!(Document::object_type(), "document");
+    assert_types::<Document>();
+    let _: NoRelations = relations_of::<User>();
+}
+
+fn relations_of<E>() -> E::Relations
+where
+    E: Entity<Relations = NoRelations>,
+{
+    NoRelations
+}
+
+#[test]
+fn derive_actor() {
+    let user_id = Uuid::now_v7();
+    assert_eq!(
+        User(user_id).to_subject(),
+        subject_reference_raw(user_id, "user", None::<String>)
+    );
+    assert_eq!(
+        SystemActor::User(user_id).to_subject(),
+        subject_reference_raw(user_id, "user", None::<String>)
+    );
+    let subject: SubjectReference = SystemActor::OrganizationMember {
+        id: "acme".to_owned(),
+    }
+    .to_subject();
+    assert_eq!(
+        subject,
+        subject_reference_raw("acme", "organization", Some("member"))
+    );
+}
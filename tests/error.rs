@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use prost::Message;
+use spicedb_rust::spicedb::ErrorReason;
+use spicedb_rust::Error;
+
+#[derive(Clone, PartialEq, Message)]
+struct RpcStatus {
+    #[prost(int32, tag = "1")]
+    code: i32,
+    #[prost(string, tag = "2")]
+    message: String,
+    #[prost(message, repeated, tag = "3")]
+    details: Vec<prost_types::Any>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ErrorInfo {
+    #[prost(string, tag = "1")]
+    reason: String,
+    #[prost(string, tag = "2")]
+    domain: String,
+    #[prost(map = "string, string", tag = "3")]
+    metadata: HashMap<String, String>,
+}
+
+/// A status as SpiceDB sends it, with an `ErrorInfo` in its details
+fn status(code: tonic::Code, reason: ErrorReason, metadata: &[(&str, &str)]) -> tonic::Status {
+    let info = ErrorInfo {
+        reason: reason.as_str_name().to_owned(),
+        domain: "authzed.com".to_owned(),
+        metadata: metadata
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+    };
+    let details = RpcStatus {
+        code: code as i32,
+        message: "failed".to_owned(),
+        details: vec![prost_types::Any {
+            type_url: "type.googleapis.com/google.rpc.ErrorInfo".to_owned(),
+            value: info.encode_to_vec(),
+        }],
+    };
+    tonic::Status::with_details(code, "failed", details.encode_to_vec().into())
+}
+
+#[test]
+fn decode_precondition_failure() {
+    let err = Error::from(status(
+        tonic::Code::FailedPrecondition,
+        ErrorReason::WriteOrDeletePreconditionFailure,
+        &[
+            ("precondition_resource_type", "document"),
+            ("precondition_resource_id", "readme"),
+            ("precondition_relation", "writer"),
+            ("precondition_subject_type", "user"),
+        ],
+    ));
+    let Error::PreconditionFailed { filter, .. } = &err else {
+        panic!("unexpected error {:?}", err);
+    };
+    assert_eq!(filter.resource_type, "document");
+    assert_eq!(filter.optional_resource_id, "readme");
+    assert_eq!(filter.optional_relation, "writer");
+    let subject_filter = filter.optional_subject_filter.as_ref().unwrap();
+    assert_eq!(subject_filter.subject_type, "user");
+    assert!(subject_filter.optional_relation.is_none());
+    assert_eq!(err.code(), tonic::Code::FailedPrecondition);
+}
+
+#[test]
+fn decode_reasons_with_metadata() {
+    let err = Error::from(status(
+        tonic::Code::FailedPrecondition,
+        ErrorReason::UnknownRelationOrPermission,
+        &[
+            ("definition_name", "document"),
+            ("relation_or_permission_name", "owner"),
+        ],
+    ));
+    assert!(
+        matches!(&err, Error::UnknownRelationOrPermission { definition, name, .. }
+            if definition == "document" && name == "owner"),
+        "{:?}",
+        err
+    );
+
+    let err = Error::from(status(
+        tonic::Code::InvalidArgument,
+        ErrorReason::TooManyUpdatesInRequest,
+        &[("maximum_updates_allowed", "1000")],
+    ));
+    assert!(
+        matches!(err, Error::TooManyUpdates { limit: 1000, .. }),
+        "{:?}",
+        err
+    );
+
+    let err = Error::from(status(
+        tonic::Code::InvalidArgument,
+        ErrorReason::InvalidCursor,
+        &[],
+    ));
+    assert!(matches!(err, Error::InvalidCursor { .. }), "{:?}", err);
+    assert_eq!(err.status().unwrap().message(), "failed");
+}
+
+#[test]
+fn statuses_without_known_reason() {
+    let err = Error::from(tonic::Status::not_found("no schema"));
+    assert!(
+        matches!(&err, Error::Status { reason: None, .. }),
+        "{:?}",
+        err
+    );
+    assert_eq!(err.code(), tonic::Code::NotFound);
+    assert_eq!(err.to_string(), "NotFound: no schema");
+
+    let err = Error::from(status(
+        tonic::Code::InvalidArgument,
+        ErrorReason::SchemaParseError,
+        &[("source_code", "definition")],
+    ));
+    let Error::Status {
+        reason, metadata, ..
+    } = &err
+    else {
+        panic!("unexpected error {:?}", err);
+    };
+    assert_eq!(*reason, Some(ErrorReason::SchemaParseError));
+    assert_eq!(metadata["source_code"], "definition");
+}
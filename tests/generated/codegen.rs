@@ -0,0 +1,167 @@
+// This file is @generated by spicedb-rust, do not edit.
+
+pub struct User;
+
+impl spicedb_rust::Entity for User {
+    type Relations = spicedb_rust::NoRelations;
+    type Id = uuid::Uuid;
+
+    fn object_type() -> &'static str {
+        "user"
+    }
+}
+
+pub struct OrgTeam;
+
+impl spicedb_rust::Entity for OrgTeam {
+    type Relations = OrgTeamRelation;
+    type Id = String;
+
+    fn object_type() -> &'static str {
+        "org/team"
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrgTeamRelation {
+    Member,
+}
+
+impl From<OrgTeamRelation> for &'static str {
+    fn from(value: OrgTeamRelation) -> Self {
+        match value {
+            OrgTeamRelation::Member => "member",
+        }
+    }
+}
+
+impl spicedb_rust::IntoEnumIterator for OrgTeamRelation {
+    type Iterator = std::array::IntoIter<Self, 1>;
+
+    fn iter() -> Self::Iterator {
+        [
+            OrgTeamRelation::Member,
+        ]
+        .into_iter()
+    }
+}
+
+impl spicedb_rust::Resource for OrgTeam {
+    type Permissions = spicedb_rust::NoPermissions;
+}
+
+pub struct Document;
+
+impl spicedb_rust::Entity for Document {
+    type Relations = DocumentRelation;
+    type Id = String;
+
+    fn object_type() -> &'static str {
+        "document"
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocumentRelation {
+    Reader,
+    Writer,
+}
+
+impl From<DocumentRelation> for &'static str {
+    fn from(value: DocumentRelation) -> Self {
+        match value {
+            DocumentRelation::Reader => "reader",
+            DocumentRelation::Writer => "writer",
+        }
+    }
+}
+
+impl spicedb_rust::IntoEnumIterator for DocumentRelation {
+    type Iterator = std::array::IntoIter<Self, 2>;
+
+    fn iter() -> Self::Iterator {
+        [
+            DocumentRelation::Reader,
+            DocumentRelation::Writer,
+        ]
+        .into_iter()
+    }
+}
+
+impl spicedb_rust::Resource for Document {
+    type Permissions = DocumentPermission;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocumentPermission {
+    Read,
+    Write,
+    Type,
+}
+
+impl From<DocumentPermission> for &'static str {
+    fn from(value: DocumentPermission) -> Self {
+        match value {
+            DocumentPermission::Read => "read",
+            DocumentPermission::Write => "write",
+            DocumentPermission::Type => "type",
+        }
+    }
+}
+
+impl spicedb_rust::IntoEnumIterator for DocumentPermission {
+    type Iterator = std::array::IntoIter<Self, 3>;
+
+    fn iter() -> Self::Iterator {
+        [
+            DocumentPermission::Read,
+            DocumentPermission::Write,
+            DocumentPermission::Type,
+        ]
+        .into_iter()
+    }
+}
+
+pub struct IpAllowlist;
+
+impl spicedb_rust::Caveat for IpAllowlist {
+    type ContextStruct = IpAllowlistContext;
+
+    fn name() -> &'static str {
+        "ip_allowlist"
+    }
+}
+
+/// Context of the `ip_allowlist` caveat, fields left as `None` are omitted so they can be provided
+/// when checking instead.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IpAllowlistContext {
+    pub user_ip: Option<String>,
+    pub allowed: Option<Vec<String>>,
+    pub type_: Option<i64>,
+}
+
+impl From<IpAllowlistContext> for spicedb_rust::prost_types::Struct {
+    fn from(context: IpAllowlistContext) -> Self {
+        let mut fields = std::collections::BTreeMap::new();
+        if let Some(value) = context.user_ip {
+            fields.insert(
+                "user_ip".to_owned(),
+                spicedb_rust::IntoContextValue::into_context_value(value),
+            );
+        }
+        if let Some(value) = context.allowed {
+            fields.insert(
+                "allowed".to_owned(),
+                spicedb_rust::IntoContextValue::into_context_value(value),
+            );
+        }
+        if let Some(value) = context.type_ {
+            fields.insert(
+                "type".to_owned(),
+                spicedb_rust::IntoContextValue::into_context_value(value),
+            );
+        }
+        Self { fields }
+    }
+}
@@ -0,0 +1,98 @@
+//! Feeds responses violating the proto `validate` rules from a stub server, the client must fail
+//! with `Error::InvalidResponse` instead of panicking.
+
+mod common;
+
+use common::stub::{token, Stub};
+use common::{Document, DocumentPermission, User};
+use spicedb_rust::spicedb::{self, ObjectReference};
+use spicedb_rust::Error;
+use tokio_stream::StreamExt;
+use uuid::Uuid;
+
+fn assert_invalid_response<T>(result: Result<T, Error>, expected: &str) {
+    match result {
+        Err(Error::InvalidResponse(message)) => {
+            assert!(message.contains(expected), "{}", message)
+        }
+        Err(err) => panic!("unexpected error {:?}", err),
+        Ok(_) => panic!("malformed response was accepted"),
+    }
+}
+
+#[tokio::test]
+async fn read_relationships_without_subject() {
+    let client = Stub {
+        read_relationships: vec![spicedb::ReadRelationshipsResponse {
+            read_at: token(),
+            relationship: Some(spicedb::Relationship {
+                resource: Some(ObjectReference {
+                    object_type: "document".to_owned(),
+                    object_id: "readme".to_owned(),
+                }),
+                relation: "reader".to_owned(),
+                subject: None,
+                optional_caveat: None,
+            }),
+            after_result_cursor: None,
+        }],
+        ..Default::default()
+    }
+    .serve()
+    .await;
+    let mut stream = Box::pin(client.read_relationships_request().send().await.unwrap());
+    assert_invalid_response(
+        stream.next().await.unwrap(),
+        "Relationship without a subject",
+    );
+}
+
+#[tokio::test]
+async fn read_schema_without_read_at() {
+    let client = Stub {
+        read_schema: Some(spicedb::ReadSchemaResponse {
+            schema_text: "definition user {}".to_owned(),
+            read_at: None,
+        }),
+        ..Default::default()
+    }
+    .serve()
+    .await;
+    assert_invalid_response(client.read_schema().await, "without read_at");
+}
+
+#[tokio::test]
+async fn lookup_resources_with_unknown_permissionship() {
+    let client = Stub {
+        lookup_resources: vec![spicedb::LookupResourcesResponse {
+            looked_up_at: token(),
+            resource_object_id: "readme".to_owned(),
+            permissionship: 42,
+            partial_caveat_info: None,
+            after_result_cursor: None,
+        }],
+        ..Default::default()
+    }
+    .serve()
+    .await;
+    let mut request = client.lookup_resources_request::<Document>();
+    request
+        .permission(DocumentPermission::Read)
+        .actor(&User::new(Uuid::nil()));
+    assert_invalid_response(request.send_collect_ids().await, "LookupPermissionship: 42");
+}
+
+#[tokio::test]
+async fn watch_without_changes_through() {
+    let client = Stub {
+        watch: vec![spicedb::WatchResponse {
+            updates: vec![],
+            changes_through: None,
+        }],
+        ..Default::default()
+    }
+    .serve()
+    .await;
+    let mut stream = Box::pin(client.watch_request().send().await.unwrap());
+    assert_invalid_response(stream.next().await.unwrap(), "without changes_through");
+}
@@ -0,0 +1,154 @@
+#![cfg(feature = "integration-test")]
+
+mod common;
+
+use std::time::Duration;
+
+use common::{Document, DocumentRelation, User};
+use spicedb_rust::migrations::{Migration, Migrator};
+use spicedb_rust::spicedb::relationship_update;
+use spicedb_rust::{Error, RelationshipOperation, SpiceDBClient};
+
+const V1: &str = "definition user {}
+
+definition document {
+    relation reader: user
+    permission read = reader
+}";
+
+fn migrator() -> Migrator {
+    Migrator::new([
+        Migration::new(1, V1),
+        Migration::new(2, include_str!("schema.zed")).backfill(|client| async move {
+            let mut request = client.create_relationships_request();
+            request.add_relationship_raw(relationship_update::<User, Document>(
+                RelationshipOperation::Touch,
+                uuid::Uuid::nil(),
+                None,
+                "readme",
+                DocumentRelation::Writer,
+            ));
+            request.send().await?;
+            Ok(())
+        }),
+    ])
+}
+
+#[tokio::test]
+async fn run_migrations() {
+    let client = SpiceDBClient::new_isolated("http://localhost:50051")
+        .await
+        .unwrap();
+    assert_eq!(Migrator::current_version(&client).await.unwrap(), 0);
+
+    let applied = migrator().run(&client).await.unwrap();
+    assert_eq!(applied, vec![1, 2]);
+    assert_eq!(Migrator::current_version(&client).await.unwrap(), 2);
+    let schema = client.read_schema().await.unwrap().schema_text;
+    assert!(schema.contains("relation writer"), "{}", schema);
+    assert!(schema.contains("definition spicedb_rust_migration"));
+
+    assert!(migrator().run(&client).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn concurrent_runs_apply_each_step_once() {
+    let client = SpiceDBClient::new_isolated("http://localhost:50051")
+        .await
+        .unwrap();
+    let (first, second) = (migrator(), migrator());
+    let (first, second) = tokio::join!(first.run(&client), second.run(&client));
+    let mut applied = first.unwrap();
+    applied.extend(second.unwrap());
+    applied.sort();
+    assert_eq!(applied, vec![1, 2]);
+}
+
+#[tokio::test]
+async fn lock_is_refreshed_while_steps_run() {
+    let client = SpiceDBClient::new_isolated("http://localhost:50051")
+        .await
+        .unwrap();
+    let slow_migrator = || {
+        let mut migrator = Migrator::new([Migration::new(1, V1).backfill(|_| async {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            Ok(())
+        })]);
+        migrator.lock_timeout(Duration::from_millis(300));
+        migrator
+    };
+    let (first, second) = (slow_migrator(), slow_migrator());
+    let (first, second) = tokio::join!(first.run(&client), second.run(&client));
+    let mut applied = first.unwrap();
+    applied.extend(second.unwrap());
+    assert_eq!(applied, vec![1]);
+}
+
+#[tokio::test]
+async fn versions_must_increase() {
+    let client = SpiceDBClient::new_isolated("http://localhost:50051")
+        .await
+        .unwrap();
+    let err = Migrator::new([Migration::new(2, V1), Migration::new(1, V1)])
+        .run(&client)
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn steps_stop_once_the_lock_is_taken_over() {
+    let client = SpiceDBClient::new_isolated("http://localhost:50051")
+        .await
+        .unwrap();
+    let err = Migrator::new([Migration::new(1, V1).cleanup(|client| async move {
+        // Another runner taking over the lock after this one failed to refresh it
+        let filter = spicedb_rust::spicedb::relationship_filter_raw(
+            "spicedb_rust_migration",
+            Some("lock"),
+            None::<String>,
+            Some("holder"),
+            None,
+        );
+        client
+            .permission_service_client()
+            .delete_relationships(spicedb_rust::spicedb::DeleteRelationshipsRequest {
+                relationship_filter: Some(filter),
+                ..Default::default()
+            })
+            .await?;
+        Ok(())
+    })])
+    .run(&client)
+    .await
+    .unwrap_err();
+    assert!(matches!(err, Error::MigrationLockLost), "{:?}", err);
+    assert_eq!(Migrator::current_version(&client).await.unwrap(), 0);
+    let schema = client.read_schema().await.unwrap().schema_text;
+    assert!(!schema.contains("definition document"), "{}", schema);
+}
+
+#[tokio::test]
+async fn bootstrap_keeps_schemas_the_parser_doesnt_support() {
+    let client = SpiceDBClient::new_isolated("http://localhost:50051")
+        .await
+        .unwrap();
+    client
+        .write_schema(
+            "definition user {}
+
+definition folder {
+    relation viewer: user
+    permission view = viewer
+}
+
+definition document {
+    relation folder: folder
+    permission view = folder.all(view)
+}"
+            .to_string(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(migrator().run(&client).await.unwrap(), vec![1, 2]);
+}
@@ -0,0 +1,157 @@
+use pretty_assertions::assert_eq;
+use spicedb_rust::schema::ast::{ExprKind, Item, SubjectTypeKind};
+use spicedb_rust::schema::parse_schema;
+
+const SCHEMA: &str = include_str!("parser.zed");
+
+#[test]
+fn parse_definitions_and_caveats() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    assert_eq!(schema.items.len(), 4);
+
+    let user = schema.definition("user").unwrap();
+    assert_eq!(user.comments, vec!["/** a user of the system */"]);
+    assert!(user.members.is_empty());
+
+    let document = schema.definition("document").unwrap();
+    let reader = document.relation("reader").unwrap();
+    let kinds: Vec<_> = reader.subject_types.iter().map(|s| &s.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            &SubjectTypeKind::Terminal,
+            &SubjectTypeKind::Wildcard,
+            &SubjectTypeKind::Relation("member".to_owned()),
+        ]
+    );
+    assert_eq!(reader.subject_types[2].definition, "org/team");
+    assert_eq!(
+        reader.subject_types[2].caveat.as_deref(),
+        Some("ip_allowlist")
+    );
+
+    let read = document.permission("read").unwrap();
+    assert_eq!(read.comments, vec!["// readers can also be writers"]);
+    assert_eq!(read.expression.references(), vec!["reader", "writer"]);
+    assert_eq!(
+        &SCHEMA[read.span.start..read.span.end],
+        "permission read = reader + writer"
+    );
+    let ExprKind::Arrow {
+        relation,
+        permission,
+    } = &document.permission("type").unwrap().expression.kind
+    else {
+        panic!("expected an arrow");
+    };
+    assert_eq!(
+        (relation.as_str(), permission.as_str()),
+        ("writer", "member")
+    );
+
+    let caveat = schema.caveat("ip_allowlist").unwrap();
+    let parameters: Vec<_> = caveat
+        .parameters
+        .iter()
+        .map(|p| format!("{} {}", p.name, p.parameter_type))
+        .collect();
+    assert_eq!(
+        parameters,
+        vec!["user_ip ipaddress", "allowed list<string>", "type int"]
+    );
+    assert_eq!(
+        caveat.expression,
+        "allowed.exists(cidr, user_ip.in_cidr(cidr)) && type != 0"
+    );
+}
+
+#[test]
+fn operator_precedence() {
+    let schema = parse_schema(
+        "definition doc {
+            permission a = w + x & y - z
+            permission b = w - (x - y)
+            permission c = parent->view + nil
+        }",
+    )
+    .unwrap();
+    let doc = schema.definition("doc").unwrap();
+    let printed: Vec<_> = doc
+        .permissions()
+        .map(|p| p.expression.to_string())
+        .collect();
+    assert_eq!(
+        printed,
+        vec!["w + x & y - z", "w - (x - y)", "parent->view + nil"]
+    );
+
+    let ExprKind::Exclusion(left, _) = &doc.permission("a").unwrap().expression.kind else {
+        panic!("`-` should bind loosest");
+    };
+    let ExprKind::Intersection(left, _) = &left.kind else {
+        panic!("`&` should bind looser than `+`");
+    };
+    assert!(matches!(left.kind, ExprKind::Union(..)));
+}
+
+#[test]
+fn pretty_print_round_trips() {
+    let text = "use expiration
+// comment
+definition   user{}
+definition document{relation viewer:user with expiration|group#member with ip and expiration
+permission view=viewer+(viewer&nil)
+  relation banned: user:*
+}
+caveat ip(allowed map<list<string>>){ allowed.size() > 0 }";
+    let schema = parse_schema(text).unwrap();
+    let printed = schema.to_string();
+    assert_eq!(
+        printed,
+        "use expiration
+
+// comment
+definition user {}
+
+definition document {
+    relation viewer: user with expiration | group#member with ip and expiration
+
+    permission view = viewer + (viewer & nil)
+
+    relation banned: user:*
+}
+
+caveat ip(allowed map<list<string>>) {
+    allowed.size() > 0
+}
+"
+    );
+    let reparsed = parse_schema(&printed).unwrap();
+    assert_eq!(reparsed.to_string(), printed);
+    assert!(matches!(reparsed.items[0], Item::Use(_)));
+
+    let printed = parse_schema(SCHEMA).unwrap().to_string();
+    assert_eq!(parse_schema(&printed).unwrap().to_string(), printed);
+}
+
+#[test]
+fn errors_point_at_the_offending_token() {
+    let err = parse_schema("definition user {}\n\ndefinition doc {\n    relation reader user\n}")
+        .unwrap_err();
+    assert_eq!(err.message, "Expected `:`, found `user`");
+    assert_eq!((err.line, err.column), (4, 21));
+    assert_eq!(
+        err.to_string(),
+        "Expected `:`, found `user` at line 4, column 21"
+    );
+
+    let err = parse_schema("definition doc {\n    permission view = viewer +\n").unwrap_err();
+    assert_eq!(err.message, "Unexpected end of schema");
+
+    let err = parse_schema("definition doc {\n    permission view = viewer + }").unwrap_err();
+    assert_eq!(
+        err.message,
+        "Expected relation, permission, `nil` or `(`, found `}`"
+    );
+    assert_eq!((err.line, err.column), (2, 32));
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "integration-test")]
+
+mod common;
+
+use common::{Document, DocumentPermission, DocumentRelation, User};
+use pretty_assertions::assert_eq;
+use spicedb_rust::spicedb::relationship_update;
+use spicedb_rust::spicedb::wrappers::{Consistency, PermissionTreeNode, Permissionship};
+use spicedb_rust::{RelationshipOperation, SpiceDBClient};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn check_bulk_permissions() {
+    let client = SpiceDBClient::new_isolated("http://localhost:50051")
+        .await
+        .unwrap();
+    let schema = include_str!("schema.zed");
+    client.write_schema(schema.to_owned()).await.unwrap();
+
+    let user_id = Uuid::now_v7();
+    let token = client
+        .create_relationships(
+            [relationship_update::<User, Document>(
+                RelationshipOperation::Touch,
+                user_id,
+                None,
+                "homework",
+                DocumentRelation::Reader,
+            )],
+            [],
+        )
+        .await
+        .unwrap();
+
+    let actor = User::new(user_id);
+    let mut request = client.check_bulk_permissions_request();
+    request.consistency(Consistency::AtLeastAsFresh(token));
+    request.add_item::<Document>(&actor, "homework", DocumentPermission::Read);
+    request.add_item::<Document>(&actor, "homework", DocumentPermission::Write);
+    request.add_item::<Document>(&actor, "manga", DocumentPermission::Read);
+    let resp = request.send().await.unwrap();
+
+    assert!(resp.checked_at.is_some());
+    let results = resp
+        .results
+        .into_iter()
+        .map(Result::unwrap)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        results,
+        vec![
+            Permissionship::HasPermission,
+            Permissionship::NoPermission,
+            Permissionship::NoPermission
+        ]
+    );
+}
+
+#[tokio::test]
+async fn expand_permission_tree() {
+    let client = SpiceDBClient::new_isolated("http://localhost:50051")
+        .await
+        .unwrap();
+    let schema = include_str!("schema.zed");
+    client.write_schema(schema.to_owned()).await.unwrap();
+
+    let reader_id = Uuid::now_v7();
+    let writer_id = Uuid::now_v7();
+    client
+        .create_relationships(
+            [
+                relationship_update::<User, Document>(
+                    RelationshipOperation::Touch,
+                    reader_id,
+                    None,
+                    "homework",
+                    DocumentRelation::Reader,
+                ),
+                relationship_update::<User, Document>(
+                    RelationshipOperation::Touch,
+                    writer_id,
+                    None,
+                    "homework",
+                    DocumentRelation::Writer,
+                ),
+            ],
+            [],
+        )
+        .await
+        .unwrap();
+
+    let resp = client
+        .expand_permission_tree::<Document>("homework", DocumentPermission::Read)
+        .await
+        .unwrap();
+    assert!(matches!(resp.tree.node, PermissionTreeNode::Union(_)));
+    assert_eq!(resp.tree.expanded_relation, "read");
+
+    let mut subject_ids = resp
+        .tree
+        .leaf_subjects()
+        .into_iter()
+        .map(|s| s.object.object_id.clone())
+        .collect::<Vec<_>>();
+    let mut expected = vec![reader_id.to_string(), writer_id.to_string()];
+    subject_ids.sort();
+    expected.sort();
+    assert_eq!(subject_ids, expected);
+}
@@ -0,0 +1,178 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use common::stub::{token, Stub};
+use common::{Document, DocumentPermission, DocumentRelation, User};
+use spicedb_rust::spicedb::{self, relationship_update};
+use spicedb_rust::{RelationshipOperation, RetryPolicy};
+use tonic::Status;
+use uuid::Uuid;
+
+fn policy() -> RetryPolicy {
+    let mut policy = RetryPolicy::default();
+    policy
+        .max_attempts(3)
+        .initial_backoff(Duration::from_millis(1));
+    policy
+}
+
+fn has_permission() -> Option<spicedb::CheckPermissionResponse> {
+    Some(spicedb::CheckPermissionResponse {
+        checked_at: token(),
+        permissionship: spicedb::check_permission_response::Permissionship::HasPermission as i32,
+        ..Default::default()
+    })
+}
+
+#[tokio::test]
+async fn transient_errors_are_retried() {
+    let stub = Stub {
+        check_permission: has_permission(),
+        ..Default::default()
+    }
+    .fail_with([Status::unavailable("down"), Status::unavailable("down")]);
+    let retries = Arc::new(Mutex::new(vec![]));
+    let mut policy = policy();
+    let observed = retries.clone();
+    policy.on_retry(move |event| {
+        observed
+            .lock()
+            .unwrap()
+            .push((event.operation, event.attempt, event.error.code()));
+    });
+    let client = stub
+        .serve_with(|b| {
+            b.retry_policy(policy);
+        })
+        .await;
+
+    let allowed = client
+        .check_permission::<Document>(&User::new(Uuid::nil()), "readme", DocumentPermission::Read)
+        .await
+        .unwrap();
+    assert!(allowed);
+    assert_eq!(stub.calls(), 3);
+    assert_eq!(
+        *retries.lock().unwrap(),
+        vec![
+            ("CheckPermission", 1, tonic::Code::Unavailable),
+            ("CheckPermission", 2, tonic::Code::Unavailable)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn gives_up_after_max_attempts_and_on_permanent_errors() {
+    let stub = Stub {
+        check_permission: has_permission(),
+        ..Default::default()
+    }
+    .fail_with((0..3).map(|_| Status::unavailable("down")));
+    let client = stub
+        .serve_with(|b| {
+            b.retry_policy(policy());
+        })
+        .await;
+    let actor = User::new(Uuid::nil());
+    let err = client
+        .check_permission::<Document>(&actor, "readme", DocumentPermission::Read)
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::Unavailable);
+    assert_eq!(stub.calls(), 3);
+
+    let stub = stub.fail_with([Status::invalid_argument("bad")]);
+    let err = client
+        .check_permission::<Document>(&actor, "readme", DocumentPermission::Read)
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::InvalidArgument);
+    assert_eq!(stub.calls(), 4);
+}
+
+#[tokio::test]
+async fn only_idempotent_writes_are_retried() {
+    let stub = Stub {
+        write_relationships: Some(spicedb::WriteRelationshipsResponse {
+            written_at: token(),
+        }),
+        ..Default::default()
+    };
+    let client = stub
+        .serve_with(|b| {
+            b.retry_policy(policy());
+        })
+        .await;
+    let update = |operation| {
+        relationship_update::<User, Document>(
+            operation,
+            Uuid::nil(),
+            None,
+            "readme",
+            DocumentRelation::Reader,
+        )
+    };
+
+    let stub = stub.fail_with([Status::unavailable("down")]);
+    client
+        .create_relationships([update(RelationshipOperation::Touch)], [])
+        .await
+        .unwrap();
+    assert_eq!(stub.calls(), 2);
+
+    let stub = stub.fail_with([Status::unavailable("down")]);
+    let err = client
+        .create_relationships([update(RelationshipOperation::Create)], [])
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::Unavailable);
+    assert_eq!(stub.calls(), 3);
+}
+
+#[tokio::test]
+async fn deletes_are_retried() {
+    let stub = Stub {
+        delete_relationships: Some(spicedb::DeleteRelationshipsResponse {
+            deleted_at: token(),
+            deletion_progress: spicedb::delete_relationships_response::DeletionProgress::Complete
+                as i32,
+        }),
+        ..Default::default()
+    }
+    .fail_with([Status::unavailable("down")]);
+    let client = stub
+        .serve_with(|b| {
+            b.retry_policy(policy());
+        })
+        .await;
+    let mut request = client.delete_relationships_request::<Document>();
+    request.with_id("readme");
+    request.send().await.unwrap();
+    assert_eq!(stub.calls(), 2);
+}
+
+#[tokio::test]
+async fn clients_without_policy_dont_retry() {
+    let stub = Stub {
+        check_permission: has_permission(),
+        ..Default::default()
+    }
+    .fail_with([Status::unavailable("down")]);
+    let client = stub.serve().await;
+    let actor = User::new(Uuid::nil());
+    assert!(client
+        .check_permission::<Document>(&actor, "readme", DocumentPermission::Read)
+        .await
+        .is_err());
+    assert_eq!(stub.calls(), 1);
+
+    let stub = stub.fail_with([Status::unavailable("down")]);
+    assert!(client
+        .with_retry_policy(policy())
+        .check_permission::<Document>(&actor, "readme", DocumentPermission::Read)
+        .await
+        .unwrap());
+    assert_eq!(stub.calls(), 3);
+}
@@ -0,0 +1,146 @@
+use pretty_assertions::assert_eq;
+use spicedb_rust::schema::{
+    Definition, PermissionDefinition, RelationDefinition, Schema, SchemaChange, SchemaMismatch,
+    SchemaRegistry,
+};
+use spicedb_rust::spicedb;
+use spicedb_rust::{EnumIter, IntoStaticStr, NoRelations, Resource};
+
+struct Folder;
+
+#[derive(IntoStaticStr, EnumIter)]
+#[strum(serialize_all = "snake_case")]
+enum FolderPermission {
+    View,
+    Parent,
+    Delete,
+}
+
+impl spicedb_rust::Entity for Folder {
+    type Relations = FolderRelation;
+    type Id = String;
+
+    fn object_type() -> &'static str {
+        "folder"
+    }
+}
+
+#[derive(IntoStaticStr, EnumIter)]
+#[strum(serialize_all = "snake_case")]
+enum FolderRelation {
+    Parent,
+    Owner,
+}
+
+impl Resource for Folder {
+    type Permissions = FolderPermission;
+}
+
+struct Organization;
+
+impl spicedb_rust::Entity for Organization {
+    type Relations = NoRelations;
+    type Id = String;
+
+    fn object_type() -> &'static str {
+        "organization"
+    }
+}
+
+struct IpCaveat;
+
+impl spicedb_rust::Caveat for IpCaveat {
+    type ContextStruct = prost_types::Struct;
+
+    fn name() -> &'static str {
+        "ip_allowlist"
+    }
+}
+
+#[test]
+fn schema_registry_reports_drift() {
+    let schema = Schema {
+        definitions: vec![Definition {
+            name: "folder".to_owned(),
+            comment: String::new(),
+            relations: vec![RelationDefinition {
+                name: "parent".to_owned(),
+                comment: String::new(),
+                subject_types: vec![],
+            }],
+            permissions: vec![PermissionDefinition {
+                name: "view".to_owned(),
+                comment: String::new(),
+            }],
+        }],
+        caveats: vec![],
+        read_at: None,
+    };
+    let mut registry = SchemaRegistry::new();
+    registry
+        .register_resource::<Folder>()
+        .register_entity::<Organization>()
+        .register_caveat::<IpCaveat>();
+    let report = registry.verify(&schema);
+    assert_eq!(
+        report.mismatches,
+        vec![
+            SchemaMismatch::MissingRelation {
+                definition: "folder".to_owned(),
+                relation: "owner".to_owned(),
+            },
+            SchemaMismatch::MissingPermission {
+                definition: "folder".to_owned(),
+                permission: "delete".to_owned(),
+            },
+            SchemaMismatch::MissingDefinition {
+                definition: "organization".to_owned(),
+            },
+            SchemaMismatch::MissingCaveat {
+                caveat: "ip_allowlist".to_owned(),
+            },
+        ]
+    );
+    assert!(report.into_result().is_err());
+}
+
+#[test]
+fn schema_diff_conversion() {
+    use spicedb::exp_schema_diff::Diff;
+
+    let diff = spicedb::ExpSchemaDiff {
+        diff: Some(Diff::RelationSubjectTypeRemoved(
+            spicedb::ExpRelationSubjectTypeChange {
+                relation: Some(spicedb::ExpRelation {
+                    name: "reader".to_owned(),
+                    parent_definition_name: "document".to_owned(),
+                    ..Default::default()
+                }),
+                changed_subject_type: Some(spicedb::ExpTypeReference {
+                    subject_definition_name: "user".to_owned(),
+                    optional_caveat_name: String::new(),
+                    typeref: Some(spicedb::exp_type_reference::Typeref::IsPublicWildcard(true)),
+                }),
+            },
+        )),
+    };
+    let change = SchemaChange::try_from(diff).unwrap();
+    assert!(change.is_destructive());
+    assert_eq!(
+        change.to_string(),
+        "- subject type user:* on relation document#reader"
+    );
+
+    let diff = spicedb::ExpSchemaDiff {
+        diff: Some(Diff::PermissionAdded(spicedb::ExpPermission {
+            name: "delete".to_owned(),
+            parent_definition_name: "document".to_owned(),
+            ..Default::default()
+        })),
+    };
+    let change = SchemaChange::try_from(diff).unwrap();
+    assert!(!change.is_destructive());
+    assert_eq!(change.to_string(), "+ permission document#delete");
+
+    assert!(SchemaChange::try_from(spicedb::ExpSchemaDiff { diff: None }).is_err());
+}
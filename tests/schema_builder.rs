@@ -0,0 +1,130 @@
+use pretty_assertions::assert_eq;
+use spicedb_rust::schema::ast::Expr;
+use spicedb_rust::schema::{
+    arrow, nil, parse_schema, perm, rel, subject, wildcard, PermissionExpr, SchemaBuildError,
+    SchemaBuilder,
+};
+use spicedb_rust::Caveat;
+
+mod common;
+use common::*;
+
+struct OnlyWeekdays;
+
+impl Caveat for OnlyWeekdays {
+    type ContextStruct = spicedb_rust::prost_types::Struct;
+
+    fn name() -> &'static str {
+        "only_weekdays"
+    }
+}
+
+fn schema() -> SchemaBuilder {
+    SchemaBuilder::new()
+        .definition::<User>(|d| d.comment("/** a user of the system */"))
+        .definition::<Document>(|d| {
+            d.relation(
+                DocumentRelation::Reader,
+                [
+                    subject::<User>(),
+                    wildcard::<User>(),
+                    subject::<User>().with_caveat::<OnlyWeekdays>(),
+                ],
+            )
+            .relation(DocumentRelation::Writer, [subject::<User>()])
+            .permission(
+                DocumentPermission::Read,
+                rel(DocumentRelation::Reader) + rel(DocumentRelation::Writer),
+            )
+            .permission(
+                DocumentPermission::Write,
+                rel(DocumentRelation::Writer) - (rel(DocumentRelation::Reader) & nil()),
+            )
+        })
+        .caveat::<OnlyWeekdays>(
+            [("weekday", "int"), ("allowed", "list<int>")],
+            "weekday in allowed",
+        )
+}
+
+#[test]
+fn render_schema() {
+    let rendered = schema().render().unwrap();
+    assert_eq!(
+        rendered,
+        "/** a user of the system */
+definition user {}
+
+definition document {
+    relation reader: user | user:* | user with only_weekdays
+    relation writer: user
+
+    permission read = reader + writer
+    permission write = writer - reader & nil
+}
+
+caveat only_weekdays(weekday int, allowed list<int>) {
+    weekday in allowed
+}
+"
+    );
+    assert_eq!(parse_schema(&rendered).unwrap().to_string(), rendered);
+}
+
+#[test]
+fn expressions_are_grouped_by_rust_precedence() {
+    let expr: PermissionExpr<DocumentRelation, DocumentPermission> =
+        perm(DocumentPermission::Write) + arrow(DocumentRelation::Reader, DocumentPermission::Read)
+            - nil();
+    assert_eq!(Expr::from(expr).to_string(), "write + reader->read - nil");
+
+    let expr: PermissionExpr<DocumentRelation, DocumentPermission> =
+        (rel(DocumentRelation::Writer) - rel(DocumentRelation::Reader)) & nil();
+    assert_eq!(Expr::from(expr).to_string(), "(writer - reader) & nil");
+}
+
+#[test]
+fn unknown_names_are_rejected() {
+    let err = SchemaBuilder::new()
+        .definition::<User>(|d| d)
+        .definition::<Document>(|d| {
+            d.relation(DocumentRelation::Reader, [subject::<User>()])
+                .permission(
+                    DocumentPermission::Read,
+                    rel(DocumentRelation::Reader) + rel(DocumentRelation::Writer),
+                )
+        })
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        SchemaBuildError::UnknownReference {
+            definition: "document".to_owned(),
+            permission: "read".to_owned(),
+            name: "writer".to_owned(),
+        }
+    );
+
+    let err = SchemaBuilder::new()
+        .definition::<Document>(|d| d.relation(DocumentRelation::Reader, [subject::<User>()]))
+        .render()
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "relation `document#reader` allows subjects of `user` which is not defined"
+    );
+}
+
+#[test]
+fn malformed_parameter_types_are_rejected() {
+    let err = SchemaBuilder::new()
+        .caveat::<OnlyWeekdays>([("allowed", "list<int")], "weekday in allowed")
+        .render()
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("parameter `allowed` of caveat `only_weekdays` has an invalid type"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "integration-test")]
+
+use spicedb_rust::schema::{nil, rel, subject, wildcard, SchemaBuilder};
+use spicedb_rust::{Caveat, SpiceDBClient};
+
+mod common;
+use common::*;
+
+struct OnlyWeekdays;
+
+impl Caveat for OnlyWeekdays {
+    type ContextStruct = spicedb_rust::prost_types::Struct;
+
+    fn name() -> &'static str {
+        "only_weekdays"
+    }
+}
+
+#[tokio::test]
+async fn write_built_schema() {
+    let client = SpiceDBClient::new_isolated("http://localhost:50051")
+        .await
+        .unwrap();
+    let schema = SchemaBuilder::new()
+        .definition::<User>(|d| d)
+        .definition::<Document>(|d| {
+            d.relation(
+                DocumentRelation::Reader,
+                [
+                    subject::<User>(),
+                    wildcard::<User>(),
+                    subject::<User>().with_caveat::<OnlyWeekdays>(),
+                ],
+            )
+            .relation(DocumentRelation::Writer, [subject::<User>()])
+            .permission(
+                DocumentPermission::Read,
+                rel(DocumentRelation::Reader) + rel(DocumentRelation::Writer),
+            )
+            .permission(
+                DocumentPermission::Write,
+                rel(DocumentRelation::Writer) - (rel(DocumentRelation::Reader) & nil()),
+            )
+        })
+        .caveat::<OnlyWeekdays>(
+            [("weekday", "int"), ("allowed", "list<int>")],
+            "weekday in allowed",
+        );
+    client.write_schema(schema.render().unwrap()).await.unwrap();
+    let schema = client.reflect_schema().await.unwrap();
+    assert!(schema
+        .definition("document")
+        .unwrap()
+        .permission("write")
+        .is_some());
+}
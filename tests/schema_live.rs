@@ -0,0 +1,125 @@
+#![cfg(feature = "integration-test")]
+
+mod common;
+
+use common::{Document, DocumentRelation, User};
+use pretty_assertions::assert_eq;
+use spicedb_rust::schema::{
+    RelationDefinition, SchemaChange, SchemaChangePolicy, SchemaRegistry, SubjectType,
+    SubjectTypeKind,
+};
+use spicedb_rust::spicedb::relationship_update;
+use spicedb_rust::{Error, RelationshipOperation, SpiceDBClient};
+
+#[tokio::test]
+async fn reflect_schema() {
+    let client = SpiceDBClient::new_isolated("http://localhost:50051")
+        .await
+        .unwrap();
+    let schema = include_str!("schema.zed");
+    client.write_schema(schema.to_owned()).await.unwrap();
+
+    let schema = client.reflect_schema().await.unwrap();
+    let document = schema.definition("document").unwrap();
+    assert_eq!(
+        document.relation("reader").unwrap().allowed_subject_types(),
+        [
+            SubjectType {
+                definition: "user".to_owned(),
+                kind: SubjectTypeKind::Terminal,
+                caveat: None,
+            },
+            SubjectType {
+                definition: "user".to_owned(),
+                kind: SubjectTypeKind::Wildcard,
+                caveat: None,
+            },
+        ]
+    );
+    assert!(document.permission("read").is_some());
+    assert!(document.permission("delete").is_none());
+    assert!(schema.definition("user").unwrap().relations.is_empty());
+    assert!(schema.caveats.is_empty());
+}
+
+#[tokio::test]
+async fn verify_schema() {
+    let client = SpiceDBClient::new_isolated("http://localhost:50051")
+        .await
+        .unwrap();
+    let schema = include_str!("schema.zed");
+    client.write_schema(schema.to_owned()).await.unwrap();
+
+    let mut registry = SchemaRegistry::new();
+    registry
+        .register_entity::<User>()
+        .register_resource::<Document>();
+    let report = client.verify_schema(&registry).await.unwrap();
+    assert!(report.is_ok(), "{}", report);
+}
+
+#[tokio::test]
+async fn plan_and_apply_schema_change() {
+    let client = SpiceDBClient::new_isolated("http://localhost:50051")
+        .await
+        .unwrap();
+    client
+        .write_schema(include_str!("schema.zed").to_owned())
+        .await
+        .unwrap();
+    client
+        .create_relationships(
+            [relationship_update::<User, Document>(
+                RelationshipOperation::Touch,
+                uuid::Uuid::now_v7(),
+                None,
+                "homework",
+                DocumentRelation::Reader,
+            )],
+            [],
+        )
+        .await
+        .unwrap();
+
+    let without_writer = "definition user {}
+
+definition document {
+    relation reader: user | user:*
+
+    permission read = reader
+    permission write = nil
+}";
+    let plan = client.plan_schema_change(without_writer).await.unwrap();
+    assert!(plan.changes.contains(&SchemaChange::RelationRemoved {
+        definition: "document".to_owned(),
+        relation: RelationDefinition {
+            name: "writer".to_owned(),
+            comment: String::new(),
+            subject_types: vec![SubjectType {
+                definition: "user".to_owned(),
+                kind: SubjectTypeKind::Terminal,
+                caveat: None,
+            }],
+        },
+    }));
+    let err = client
+        .apply_schema_change(&plan, SchemaChangePolicy::Strict)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::SchemaChangeRefused(_)), "{}", err);
+    // No relationship uses `writer`, so the removal is safe
+    client
+        .apply_schema_change(&plan, SchemaChangePolicy::AllowUnused)
+        .await
+        .unwrap();
+
+    let without_reader = without_writer
+        .replace("    relation reader: user | user:*\n", "")
+        .replace("reader", "nil");
+    let plan = client.plan_schema_change(without_reader).await.unwrap();
+    let err = client
+        .apply_schema_change(&plan, SchemaChangePolicy::AllowUnused)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::SchemaChangeRefused(_)), "{}", err);
+}
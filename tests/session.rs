@@ -0,0 +1,130 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::stub::Stub;
+use common::{Document, DocumentPermission, DocumentRelation, User};
+use spicedb_rust::session::{InMemoryZedTokenStore, ZedTokenStore};
+use spicedb_rust::spicedb::{self, consistency::Requirement, relationship_update, ZedToken};
+use spicedb_rust::RelationshipOperation;
+use uuid::Uuid;
+
+fn written() -> ZedToken {
+    ZedToken {
+        token: "written".to_owned(),
+    }
+}
+
+fn at_least_as_fresh(token: ZedToken) -> Option<spicedb::Consistency> {
+    Some(spicedb::Consistency {
+        requirement: Some(Requirement::AtLeastAsFresh(token)),
+    })
+}
+
+fn stub() -> Stub {
+    Stub {
+        write_relationships: Some(spicedb::WriteRelationshipsResponse {
+            written_at: Some(written()),
+        }),
+        check_permission: Some(spicedb::CheckPermissionResponse {
+            checked_at: Some(written()),
+            permissionship: spicedb::check_permission_response::Permissionship::HasPermission
+                as i32,
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn update() -> spicedb::RelationshipUpdate {
+    relationship_update::<User, Document>(
+        RelationshipOperation::Touch,
+        Uuid::nil(),
+        None,
+        "readme",
+        DocumentRelation::Reader,
+    )
+}
+
+#[tokio::test]
+async fn reads_are_at_least_as_fresh_as_session_writes() {
+    let stub = stub();
+    let client = stub.serve().await;
+    let actor = User::new(Uuid::nil());
+    let alice = client.session("alice");
+
+    alice
+        .check_permission::<Document>(&actor, "readme", DocumentPermission::Read)
+        .await
+        .unwrap();
+    alice.create_relationships([update()], []).await.unwrap();
+    alice
+        .check_permission::<Document>(&actor, "readme", DocumentPermission::Read)
+        .await
+        .unwrap();
+    client
+        .session("bob")
+        .check_permission::<Document>(&actor, "readme", DocumentPermission::Read)
+        .await
+        .unwrap();
+    client
+        .check_permission::<Document>(&actor, "readme", DocumentPermission::Read)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        stub.check_consistencies(),
+        vec![None, at_least_as_fresh(written()), None, None]
+    );
+}
+
+#[tokio::test]
+async fn explicit_consistency_wins() {
+    let stub = stub();
+    let client = stub.serve().await.session("alice");
+    client.create_relationships([update()], []).await.unwrap();
+
+    let explicit = ZedToken {
+        token: "explicit".to_owned(),
+    };
+    client
+        .check_permission_at::<Document>(
+            &User::new(Uuid::nil()),
+            "readme",
+            DocumentPermission::Read,
+            explicit.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        stub.check_consistencies(),
+        vec![at_least_as_fresh(explicit)]
+    );
+}
+
+#[tokio::test]
+async fn sessions_share_the_configured_store() {
+    let store = Arc::new(InMemoryZedTokenStore::new());
+    let stub = stub();
+    let shared = store.clone();
+    let client = stub
+        .serve_with(|b| {
+            b.token_store(shared);
+        })
+        .await;
+
+    client
+        .session("alice")
+        .create_relationships([update()], [])
+        .await
+        .unwrap();
+    assert_eq!(store.get("alice").await.unwrap(), Some(written()));
+
+    store.remove("alice");
+    client
+        .session("alice")
+        .check_permission::<Document>(&User::new(Uuid::nil()), "readme", DocumentPermission::Read)
+        .await
+        .unwrap();
+    assert_eq!(stub.check_consistencies(), vec![None]);
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use std::time::Duration;
+
+use common::stub::{token, Stub};
+use common::{Document, DocumentPermission, User};
+use futures::future::join_all;
+use spicedb_rust::spicedb;
+use spicedb_rust::SpiceDBClient;
+use tonic::Status;
+use uuid::Uuid;
+
+fn stub() -> Stub {
+    Stub {
+        check_permission: Some(spicedb::CheckPermissionResponse {
+            checked_at: token(),
+            permissionship: spicedb::check_permission_response::Permissionship::HasPermission
+                as i32,
+            ..Default::default()
+        }),
+        lookup_subjects: vec![spicedb::LookupSubjectsResponse {
+            subject: Some(spicedb::ResolvedSubject {
+                subject_object_id: Uuid::nil().to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }],
+        delay: Duration::from_millis(50),
+        ..Default::default()
+    }
+}
+
+async fn single_flight_client(stub: &Stub) -> SpiceDBClient {
+    stub.serve_with(|b| {
+        b.single_flight(true);
+    })
+    .await
+}
+
+#[tokio::test]
+async fn identical_checks_share_one_call() {
+    let stub = stub();
+    let client = single_flight_client(&stub).await;
+    let actor = User::new(Uuid::nil());
+
+    let results = join_all(
+        ["readme", "readme", "readme", "other"]
+            .map(|id| client.check_permission::<Document>(&actor, id, DocumentPermission::Read)),
+    )
+    .await;
+
+    assert!(results.into_iter().all(|allowed| allowed.unwrap()));
+    assert_eq!(stub.calls(), 2);
+}
+
+#[tokio::test]
+async fn identical_lookups_share_one_call_while_in_flight() {
+    let stub = stub();
+    let client = single_flight_client(&stub).await;
+    let lookup = || client.lookup_subjects::<User, Document>("readme", DocumentPermission::Read);
+
+    let results = join_all((0..3).map(|_| lookup())).await;
+    for ids in results {
+        assert_eq!(ids.unwrap(), vec![Uuid::nil()]);
+    }
+    assert_eq!(stub.calls(), 1);
+
+    lookup().await.unwrap();
+    assert_eq!(stub.calls(), 2);
+}
+
+async fn check(client: &SpiceDBClient) -> Result<bool, spicedb_rust::Error> {
+    client
+        .check_permission::<Document>(&User::new(Uuid::nil()), "readme", DocumentPermission::Read)
+        .await
+}
+
+#[tokio::test]
+async fn errors_are_shared_and_clients_without_single_flight_are_unaffected() {
+    let stub = stub().fail_with([Status::unavailable("down")]);
+    let client = single_flight_client(&stub).await;
+
+    let results = join_all((0..2).map(|_| check(&client))).await;
+    for result in results {
+        assert_eq!(result.unwrap_err().code(), tonic::Code::Unavailable);
+    }
+    assert_eq!(stub.calls(), 1);
+
+    let client = stub.serve().await;
+    let results = join_all((0..2).map(|_| check(&client))).await;
+    assert!(results.into_iter().all(|allowed| allowed.unwrap()));
+    assert_eq!(stub.calls(), 3);
+}
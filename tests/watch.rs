@@ -0,0 +1,18 @@
+use pretty_assertions::assert_eq;
+use spicedb_rust::spicedb::ZedToken;
+use spicedb_rust::watch::{CheckpointStore, FileCheckpointStore};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn file_checkpoint_store_roundtrip() {
+    let path = std::env::temp_dir().join(format!("spicedb-rust-{}.checkpoint", Uuid::now_v7()));
+    let store = FileCheckpointStore::new(&path);
+    assert_eq!(store.load().await.unwrap(), None);
+
+    let token = ZedToken {
+        token: "GhUKEzE3MjEyMzQ1Njc4OTAwMDAwMDA=".to_owned(),
+    };
+    store.save(&token).await.unwrap();
+    assert_eq!(store.load().await.unwrap(), Some(token));
+    std::fs::remove_file(path).unwrap();
+}
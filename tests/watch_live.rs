@@ -0,0 +1,120 @@
+#![cfg(feature = "integration-test")]
+
+mod common;
+
+use common::{Document, DocumentRelation, User};
+use pretty_assertions::assert_eq;
+use spicedb_rust::spicedb::relationship_update;
+use spicedb_rust::watch::{CheckpointStore, InMemoryCheckpointStore};
+use spicedb_rust::{Entity, RelationshipOperation, SpiceDBClient};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_stream::StreamExt;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn watch_from_token() {
+    let client = SpiceDBClient::new_isolated("http://localhost:50051")
+        .await
+        .unwrap();
+    let schema = include_str!("schema.zed");
+    client.write_schema(schema.to_owned()).await.unwrap();
+
+    let user_id = Uuid::now_v7();
+    let start = client
+        .create_relationships(
+            [relationship_update::<User, Document>(
+                RelationshipOperation::Touch,
+                user_id,
+                None,
+                "homework",
+                DocumentRelation::Writer,
+            )],
+            [],
+        )
+        .await
+        .unwrap();
+    client
+        .create_relationships(
+            [relationship_update::<User, Document>(
+                RelationshipOperation::Touch,
+                user_id,
+                None,
+                "manga",
+                DocumentRelation::Reader,
+            )],
+            [],
+        )
+        .await
+        .unwrap();
+
+    let mut request = client.watch_request();
+    request.add_object_type::<Document>();
+    request.with_start_cursor(start);
+    let mut stream = Box::pin(request.send().await.unwrap());
+    let resp = stream.next().await.unwrap().unwrap();
+    assert_eq!(resp.updates.len(), 1);
+    let update = &resp.updates[0];
+    assert_eq!(update.operation, RelationshipOperation::Touch);
+    assert_eq!(
+        update.relationship.resource.object_type,
+        Document::object_type()
+    );
+    assert_eq!(update.relationship.resource.object_id, "manga");
+    assert_eq!(update.relationship.relation, "reader");
+    assert_eq!(
+        update.relationship.subject.object.object_id,
+        user_id.to_string()
+    );
+}
+
+#[tokio::test]
+async fn watcher_checkpoints_processed_updates() {
+    let client = SpiceDBClient::new_isolated("http://localhost:50051")
+        .await
+        .unwrap();
+    let schema = include_str!("schema.zed");
+    client.write_schema(schema.to_owned()).await.unwrap();
+
+    let user_id = Uuid::now_v7();
+    let start = client
+        .create_relationships(
+            [relationship_update::<User, Document>(
+                RelationshipOperation::Touch,
+                user_id,
+                None,
+                "homework",
+                DocumentRelation::Writer,
+            )],
+            [],
+        )
+        .await
+        .unwrap();
+    client
+        .create_relationships(
+            [relationship_update::<User, Document>(
+                RelationshipOperation::Touch,
+                user_id,
+                None,
+                "manga",
+                DocumentRelation::Reader,
+            )],
+            [],
+        )
+        .await
+        .unwrap();
+
+    let store = Arc::new(InMemoryCheckpointStore::new());
+    let mut watcher = client.watcher(store.clone());
+    watcher.add_object_type::<Document>();
+    watcher.with_start_cursor(start);
+    let mut stream = Box::pin(watcher.start().await.unwrap());
+    let resp = stream.next().await.unwrap().unwrap();
+    assert_eq!(resp.updates[0].relationship.resource.object_id, "manga");
+    assert_eq!(store.load().await.unwrap(), None);
+
+    // Asking for the next update marks the previous one as processed
+    let next = tokio::time::timeout(Duration::from_millis(500), stream.next()).await;
+    assert!(next.is_err(), "No further updates expected");
+    assert_eq!(store.load().await.unwrap(), Some(resp.changes_through));
+}